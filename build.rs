@@ -0,0 +1,70 @@
+//! Bakes information about the build into the binary, for `--version --verbose`.
+//! Anything we can't find out is reported as "unknown" rather than failing the build.
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const UNKNOWN: &str = "unknown";
+
+fn main() {
+    println!("cargo:rustc-env=VVLANG_GIT_HASH={}", git_hash());
+    println!("cargo:rustc-env=VVLANG_BUILD_DATE={}", build_date());
+    println!(
+        "cargo:rustc-env=VVLANG_TARGET={}",
+        std::env::var("TARGET").unwrap_or_else(|_| UNKNOWN.to_owned())
+    );
+
+    // Pick up new commits, without rebuilding on every change otherwise
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+/// The short hash of the commit being built, if we're in a git checkout.
+fn git_hash() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_owned())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| UNKNOWN.to_owned())
+}
+
+/// Today's date as YYYY-MM-DD, or the one in SOURCE_DATE_EPOCH
+/// for reproducible builds.
+fn build_date() -> String {
+    let seconds = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.parse::<u64>().ok(),
+        Err(_) => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|duration| duration.as_secs()),
+    };
+
+    match seconds {
+        Some(seconds) => {
+            let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+            format!("{year:04}-{month:02}-{day:02}")
+        }
+        None => UNKNOWN.to_owned(),
+    }
+}
+
+/// Convert a number of days since 1970-01-01 to a (year, month, day) date.
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,411 @@
+pub use vvlang::diagnostics::ColorChoice;
+use vvlang::highlight::HighlightFormat;
+
+/// Text printed by `--help` and on usage errors
+pub const USAGE: &str = "\
+Usage: vvlang [OPTIONS] [FILE...]
+       vvlang fmt [--write | --check] [FILE...]
+       vvlang highlight [--format ansi|html] [FILE...]
+
+Run the vvlang scripts in the given FILEs, in order, or read the script
+from stdin when FILE is '-' or when code is piped in. Without FILE, start
+the interactive REPL.
+Each script runs on its own, and the first one failing stops the others
+from running. With --check, all of the files are checked regardless.
+
+'vvlang fmt' prints the FILEs formatted in the canonical style instead.
+With --write, the FILEs are formatted in place, while with --check they're
+left untouched and the command fails if any of them isn't formatted.
+
+'vvlang highlight' prints the FILEs with their tokens colored, either with
+ANSI escape codes (default) or as HTML.
+
+Options:
+  --tokens    Print the tokens of the script, one per line
+  --ast       Print the parsed statements of the script
+  --emit <FORMAT>
+              Print the parsed statements in another format: 'dot' for
+              a Graphviz graph, EG: --emit dot script.vv | dot -Tpng > ast.png
+  --run       Run the script (default)
+  --check     Only parse the given files and report any error found
+  --time      Print how long each stage of the pipeline took
+  --watch     Run the FILEs again whenever one of them changes
+  --format <FORMAT>
+              Output format of --tokens and --ast: 'text' (default) or 'json'
+  --pretty    Indent the JSON output
+  --diagnostics <FORMAT>
+              How --check reports errors: 'text' (default) or 'json',
+              one JSON object per line on stdout
+              JSON output is only available when vvlang is built with
+              the 'serde' feature
+  --color <WHEN>
+              Color the diagnostics: 'auto' (default), 'always' or 'never'
+  --no-color  Same as --color never
+  --no-history
+              Don't save the history of the REPL between sessions
+  --no-rc     Don't run the script in $VVLANG_RC, or else in
+              ~/.config/vvlang/init.vv, when the REPL starts
+  -v, --verbose
+              Log what each stage of the pipeline does, repeat for more details
+  -q, --quiet Don't log anything, not even warnings
+  -V, --version
+              Print the version, and how it was built with --verbose
+  -h, --help  Print this help
+
+Exit status:
+  0   Success
+  1   Runtime error while running the script
+  2   The script has syntax errors
+  3   With 'fmt --check', some files aren't formatted
+  64  Invalid command-line arguments
+  66  An input file could not be read
+  73  With 'fmt --write', a file could not be written";
+
+/// Why a JSON format was refused
+#[cfg(not(feature = "serde"))]
+const JSON_NEEDS_SERDE: &str =
+    "JSON output is not available: vvlang was built without the 'serde' feature";
+
+/// The version of vvlang, EG: 'vvlang 0.1.0'.
+/// With ``verbose``, it's followed by the commit, date and target
+/// it was built from, one per line.
+pub fn version(verbose: bool) -> String {
+    let version = format!("vvlang {}", env!("CARGO_PKG_VERSION"));
+    if !verbose {
+        return version;
+    }
+
+    format!(
+        "{version}\ncommit: {}\nbuilt: {}\ntarget: {}",
+        env!("VVLANG_GIT_HASH"),
+        env!("VVLANG_BUILD_DATE"),
+        env!("VVLANG_TARGET"),
+    )
+}
+
+/// What the REPL greets the user with, including how it was built.
+pub fn banner() -> String {
+    format!(
+        "Welcome to vvlang {} ({}, built {} for {})!",
+        env!("CARGO_PKG_VERSION"),
+        env!("VVLANG_GIT_HASH"),
+        env!("VVLANG_BUILD_DATE"),
+        env!("VVLANG_TARGET"),
+    )
+}
+
+/// How the process exits, so that scripts and CI can tell failures apart.
+/// The codes for usage and input errors come from sysexits.h.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitStatus {
+    Success = 0,
+    #[allow(dead_code)]
+    RuntimeError = 1,
+    ParseError = 2,
+    Unformatted = 3,
+    Usage = 64,
+    NoInput = 66,
+    CantCreate = 73,
+}
+
+impl ExitStatus {
+    /// Terminate the process with this status.
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
+
+/// What to do with the given source code
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    /// Only lex the source and print the tokens
+    Tokens,
+    /// Parse the source and print the statements
+    Ast,
+    /// Parse the source and print the statements as a Graphviz graph
+    Dot,
+    /// Go through the whole pipeline
+    Run,
+    /// Parse every given file, without running anything
+    Check,
+    /// Print the given files in the canonical style
+    Format(FormatAction),
+    /// Print the given files with their tokens colored
+    Highlight(HighlightFormat),
+}
+
+/// What 'vvlang fmt' does with the formatted code
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatAction {
+    /// Print it to stdout
+    Print,
+    /// Replace the original files with it
+    Write,
+    /// Only tell whether it differs from the original
+    Check,
+}
+
+/// Where to read the source code from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    File(String),
+    Stdin,
+}
+
+/// How to print the output of --tokens and --ast, and any error found
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// serde-serialized data, for other tools to consume
+    #[cfg(feature = "serde")]
+    Json { pretty: bool },
+}
+
+/// How --check reports the errors it finds
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DiagnosticsFormat {
+    /// Human-readable text on stderr
+    #[default]
+    Text,
+    /// One JSON object per error on stdout, for editors
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+/// The parsed command-line arguments
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Args {
+    /// The pipeline stage explicitly requested by the user, if any
+    pub mode: Option<Mode>,
+    /// The sources to process, in order
+    pub inputs: Vec<Input>,
+    pub format: OutputFormat,
+    pub diagnostics: DiagnosticsFormat,
+    pub color: ColorChoice,
+    /// Report how long each stage of the pipeline takes
+    pub time: bool,
+    /// Run the inputs again whenever they change
+    pub watch: bool,
+    /// Don't persist the history of the REPL
+    pub no_history: bool,
+    /// Don't run the rc file when the REPL starts
+    pub no_rc: bool,
+    /// How many times -v was given
+    pub verbose: u8,
+    /// Don't log anything
+    pub quiet: bool,
+    pub version: bool,
+    pub help: bool,
+}
+
+impl Args {
+    /// Parse the given command-line arguments (without the program name).
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut json = false;
+        let mut pretty = false;
+
+        let mut args = args.into_iter().peekable();
+        let subcommand = match args.peek().map(String::as_str) {
+            Some("fmt") => Some("fmt"),
+            Some("highlight") => Some("highlight"),
+            _ => None,
+        };
+        parsed.mode = match subcommand {
+            Some("fmt") => Some(Mode::Format(FormatAction::Print)),
+            Some(_) => Some(Mode::Highlight(HighlightFormat::Ansi)),
+            None => None,
+        };
+        if subcommand.is_some() {
+            args.next();
+        }
+        let formatting = subcommand == Some("fmt");
+        let highlighting = subcommand == Some("highlight");
+
+        while let Some(arg) = args.next() {
+            let mode = match arg.as_str() {
+                "--write" if formatting => Mode::Format(FormatAction::Write),
+                "--check" if formatting => Mode::Format(FormatAction::Check),
+                "--tokens" => Mode::Tokens,
+                "--ast" => Mode::Ast,
+                "--emit" => parse_emit(args.next().as_deref())?,
+                emit if emit.starts_with("--emit=") => parse_emit(Some(&emit["--emit=".len()..]))?,
+                "--run" => Mode::Run,
+                "--check" => Mode::Check,
+                "--format" if highlighting => {
+                    let format = match args.next().as_deref() {
+                        Some("ansi") => HighlightFormat::Ansi,
+                        Some("html") => HighlightFormat::Html,
+                        Some(other) => return Err(format!("Unknown highlight format '{other}'")),
+                        None => return Err("--format requires a value".to_owned()),
+                    };
+                    parsed.mode = Some(Mode::Highlight(format));
+                    continue;
+                }
+                "--format" => {
+                    json = match args.next().as_deref() {
+                        Some("text") => false,
+                        #[cfg(feature = "serde")]
+                        Some("json") => true,
+                        #[cfg(not(feature = "serde"))]
+                        Some("json") => return Err(JSON_NEEDS_SERDE.to_owned()),
+                        Some(other) => return Err(format!("Unknown output format '{other}'")),
+                        None => return Err("--format requires a value".to_owned()),
+                    };
+                    continue;
+                }
+                "--diagnostics" => {
+                    parsed.diagnostics = match args.next().as_deref() {
+                        Some("text") => DiagnosticsFormat::Text,
+                        #[cfg(feature = "serde")]
+                        Some("json") => DiagnosticsFormat::Json,
+                        #[cfg(not(feature = "serde"))]
+                        Some("json") => return Err(JSON_NEEDS_SERDE.to_owned()),
+                        Some(other) => return Err(format!("Unknown diagnostics format '{other}'")),
+                        None => return Err("--diagnostics requires a value".to_owned()),
+                    };
+                    continue;
+                }
+                "--color" => {
+                    parsed.color = match args.next().as_deref() {
+                        Some("auto") => ColorChoice::Auto,
+                        Some("always") => ColorChoice::Always,
+                        Some("never") => ColorChoice::Never,
+                        Some(other) => return Err(format!("Unknown color choice '{other}'")),
+                        None => return Err("--color requires a value".to_owned()),
+                    };
+                    continue;
+                }
+                "--time" => {
+                    parsed.time = true;
+                    continue;
+                }
+                "--watch" => {
+                    parsed.watch = true;
+                    continue;
+                }
+                "--no-color" => {
+                    parsed.color = ColorChoice::Never;
+                    continue;
+                }
+                "--pretty" => {
+                    pretty = true;
+                    continue;
+                }
+                "--no-history" => {
+                    parsed.no_history = true;
+                    continue;
+                }
+                "--no-rc" => {
+                    parsed.no_rc = true;
+                    continue;
+                }
+                "-v" | "--verbose" => {
+                    parsed.verbose = parsed.verbose.saturating_add(1);
+                    continue;
+                }
+                "-vv" => {
+                    parsed.verbose = parsed.verbose.saturating_add(2);
+                    continue;
+                }
+                "-q" | "--quiet" => {
+                    parsed.quiet = true;
+                    continue;
+                }
+                "-V" | "--version" => {
+                    parsed.version = true;
+                    continue;
+                }
+                "-h" | "--help" => {
+                    parsed.help = true;
+                    continue;
+                }
+                "-" => {
+                    parsed.inputs.push(Input::Stdin);
+                    continue;
+                }
+                flag if flag.starts_with('-') => {
+                    return Err(format!("Unknown option '{flag}'"));
+                }
+                path => {
+                    parsed.inputs.push(Input::File(path.to_owned()));
+                    continue;
+                }
+            };
+
+            if let Some(name) = subcommand {
+                if !matches!(mode, Mode::Format(_)) {
+                    return Err(format!("'{arg}' can't be used with {name}"));
+                }
+            }
+
+            if let Some(previous_mode) = &parsed.mode {
+                if formatting {
+                    if previous_mode != &Mode::Format(FormatAction::Print) && previous_mode != &mode
+                    {
+                        return Err("Only one of --write and --check can be used".to_owned());
+                    }
+                } else if previous_mode != &mode {
+                    return Err(
+                        "Only one of --tokens, --ast, --emit, --run and --check can be used"
+                            .to_owned(),
+                    );
+                }
+            }
+            parsed.mode = Some(mode);
+        }
+
+        if parsed.quiet && parsed.verbose > 0 {
+            return Err("Only one of --verbose and --quiet can be used".to_owned());
+        }
+
+        if parsed.watch && (parsed.inputs.is_empty() || parsed.inputs.contains(&Input::Stdin)) {
+            return Err("--watch can only be used with files".to_owned());
+        }
+        if let Some(name) = subcommand.filter(|_| parsed.watch) {
+            return Err(format!("--watch can't be used with {name}"));
+        }
+        if parsed.mode == Some(Mode::Format(FormatAction::Write))
+            && (parsed.inputs.is_empty() || parsed.inputs.contains(&Input::Stdin))
+        {
+            return Err("--write can only be used with files".to_owned());
+        }
+
+        #[cfg(feature = "serde")]
+        if parsed.diagnostics == DiagnosticsFormat::Json && parsed.mode != Some(Mode::Check) {
+            return Err("--diagnostics json can only be used with --check".to_owned());
+        }
+
+        if json {
+            if !matches!(parsed.mode, Some(Mode::Tokens | Mode::Ast)) {
+                return Err("--format json can only be used with --tokens or --ast".to_owned());
+            }
+            if parsed.inputs.len() > 1 {
+                return Err("--format json can only be used with a single input".to_owned());
+            }
+            #[cfg(feature = "serde")]
+            {
+                parsed.format = OutputFormat::Json { pretty };
+            }
+        } else if pretty {
+            return Err("--pretty can only be used with --format json".to_owned());
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// The mode asked for by the value of --emit.
+fn parse_emit(value: Option<&str>) -> Result<Mode, String> {
+    match value {
+        Some("dot") => Ok(Mode::Dot),
+        Some(other) => Err(format!("Unknown emit format '{other}'")),
+        None => Err("--emit requires a value".to_owned()),
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/cli.rs"]
+mod cli_tests;
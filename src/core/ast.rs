@@ -0,0 +1,312 @@
+//! The nodes of the tree built by the parser.
+//! Their fields are public, so that the passes working on the tree
+//! (EG: the formatter) can match on them and take them apart.
+//! The exception is ``Program``, whose statements go through its methods.
+
+use std::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::core::span::Spanned;
+use crate::core::tokens::{Token, TokenType};
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+/// A 'let' assignment of the form:
+/// let <identifier> = <expression>;
+/// EG:
+///   let x = 5;
+///   let x = add(5 + 5);
+pub struct LetStatement {
+    /// The 'let' keyword.
+    pub token: Token,
+    pub identifier: Identifier,
+    pub value: Spanned<Expression>,
+}
+
+impl LetStatement {
+    /// The expression bound by this statement.
+    pub fn value_expr(&self) -> &Expression {
+        &self.value.node
+    }
+}
+
+/// An assignment to an existing binding, of the form:
+/// <identifier> = <expression>;
+/// Compound assignments are desugared into plain ones, see
+/// ``desugar_compound_assign``.
+/// EG:
+///   x = 5;
+///   x += 1; -> x = x + (1);
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AssignStatement {
+    pub token: Token,
+    pub identifier: Identifier,
+    /// The operator as written in the source, EG: '+=' for 'x += 1;'.
+    pub operator: Token,
+    pub value: Spanned<Expression>,
+}
+
+impl AssignStatement {
+    /// The tokens of the value as written in the source, before it was
+    /// desugared, EG: '1' for 'x += 1;'.
+    /// A value that wasn't desugared (EG: built by hand) is returned whole.
+    pub fn written_value(&self) -> &[Token] {
+        let tokens = &self.value.tokens;
+        if self.operator.r#type.compound_operator().is_none() {
+            return tokens;
+        }
+        // Leave out the 'x + (' and ')' added around the value
+        tokens
+            .get(3..tokens.len().saturating_sub(1))
+            .unwrap_or(tokens)
+    }
+}
+
+/// A 'return' assignment of the form:
+/// return <expression>;
+/// The expression can be left out, in which case it's null.
+/// EG:
+///   return 5;
+///   return add(5 + 5);
+///   return;
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ReturnStatement {
+    pub token: Token,
+    pub value: Spanned<Expression>,
+}
+
+/// Represents the binding of a variable.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Identifier {
+    /// The name of the variable.
+    /// EG: let x = 10; -> 'x'
+    pub name: String,
+}
+
+/// A statement consisting of a single expression.
+/// EG:
+///   5;
+///   x + 10;
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ExpressionStatement {
+    pub token: Token,
+    pub expression: Spanned<Expression>,
+}
+
+/// Anything that returns a value.
+/// EG:
+///   5;
+///   2+2;
+///   add(1, 2);
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Expression {
+    // pub token: Token,
+    pub tokens: Vec<Token>,
+}
+
+impl Expression {
+    /// The expression standing for no value at all, EG: in 'return;'
+    pub fn null() -> Expression {
+        Expression { tokens: Vec::new() }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// The tokens of the expression joined by spaces, all on one line
+    /// even if the expression spans several lines in the source.
+    pub fn literal(&self) -> String {
+        let exp_literal = self
+            .tokens
+            .iter()
+            .filter(|&t| !matches!(t.r#type, TokenType::Semicolon | TokenType::NewLine))
+            .map(|t| t.literal.clone())
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        exp_literal
+    }
+}
+
+/// Using the jergon of the Book, a 'Statement' is basically a
+/// single node of the Abtract Syntax Tree.
+/// We support 4 main types of Statements:
+/// A 'let' assignment, an assignment to an existing binding,
+/// a 'return' statement and a simple Expression.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Statement {
+    Assignment(LetStatement),
+    Reassignment(AssignStatement),
+    Return(ReturnStatement),
+    SingleExpression(ExpressionStatement),
+}
+
+impl Statement {
+    /// The token the statement starts with, EG: the 'let' keyword.
+    pub fn token(&self) -> &Token {
+        match self {
+            Statement::Assignment(let_statement) => &let_statement.token,
+            Statement::Reassignment(assign_statement) => &assign_statement.token,
+            Statement::Return(return_statement) => &return_statement.token,
+            Statement::SingleExpression(expression) => &expression.token,
+        }
+    }
+
+    fn token_literal(&self) -> String {
+        self.token().literal.to_owned()
+    }
+
+    /// The first and last lines of the source the statement was parsed from.
+    pub fn lines(&self) -> std::ops::RangeInclusive<usize> {
+        let (token, value) = match self {
+            Statement::Assignment(s) => (&s.token, &s.value),
+            Statement::Reassignment(s) => (&s.token, &s.value),
+            Statement::Return(s) => (&s.token, &s.value),
+            Statement::SingleExpression(s) => (&s.token, &s.expression),
+        };
+        let last_line = value.tokens.iter().map(|t| t.line).max();
+        token.line..=last_line.unwrap_or(token.line).max(token.line)
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Statement::Assignment(let_statement) => {
+                format!(
+                    "let {} = {};",
+                    let_statement.identifier.name,
+                    let_statement.value.literal()
+                )
+            }
+            Statement::Reassignment(assign_statement) => {
+                format!(
+                    "{} = {};",
+                    self.token_literal(),
+                    assign_statement.value.literal()
+                )
+            }
+            Statement::Return(return_statement) if return_statement.value.is_null() => {
+                "return;".to_owned()
+            }
+            Statement::Return(return_statement) => {
+                format!("return {};", return_statement.value.literal())
+            }
+            Statement::SingleExpression(_) => {
+                //
+                self.token_literal()
+            }
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Program {
+    statements: Vec<Statement>,
+}
+
+impl Program {
+    pub fn new() -> Program {
+        Program {
+            statements: Vec::new(),
+        }
+    }
+
+    /// Create an empty program with room for ``capacity`` statements.
+    pub fn with_capacity(capacity: usize) -> Program {
+        Program {
+            statements: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Add ``statement`` at the end of the program.
+    pub fn push(&mut self, statement: Statement) {
+        self.statements.push(statement);
+    }
+
+    /// All the statements, in order.
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    /// How many statements the program has.
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// The statement at ``index``, if there's one.
+    pub fn get(&self, index: usize) -> Option<&Statement> {
+        self.statements.get(index)
+    }
+
+    pub fn first(&self) -> Option<&Statement> {
+        self.statements.first()
+    }
+
+    pub fn last(&self) -> Option<&Statement> {
+        self.statements.last()
+    }
+
+    /// Iterate over the statements, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Statement> {
+        self.statements.iter()
+    }
+
+    /// The statements that cover the given ``line`` of the source,
+    /// in order. A statement spanning several lines covers all of them.
+    pub fn statements_on_line(&self, line: usize) -> Vec<&Statement> {
+        self.statements
+            .iter()
+            .filter(|statement| statement.lines().contains(&line))
+            .collect()
+    }
+}
+
+/// Iterate over the statements of a program, EG:
+///   for statement in &program { ... }
+impl IntoIterator for Program {
+    type Item = Statement;
+    type IntoIter = std::vec::IntoIter<Statement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Program {
+    type Item = &'a Statement;
+    type IntoIter = std::slice::Iter<'a, Statement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Program {
+    type Item = &'a mut Statement;
+    type IntoIter = std::slice::IterMut<'a, Statement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.iter_mut()
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/ast.rs"]
+mod ast_tests;
@@ -1,6 +1,8 @@
-use color_eyre::eyre;
+use std::collections::VecDeque;
+
 use lazy_static::lazy_static;
 use phf::phf_map;
+use thiserror::Error;
 
 use crate::core::tokens::{Token, TokenType};
 
@@ -8,21 +10,21 @@ lazy_static! {
 
     /// Characters considered valid to be used in identifiers
     pub static ref LETTERS: Vec<char> = {
-        let letters =
-            // Extra supported chars
-            std::iter::once(b'_')
+        // Extra supported chars
+        std::iter::once(b'_')
             // Any lower/upper case alphabetic char
-            .chain(b'a'..b'z')
-            .chain(b'A'..b'Z')
+            .chain(b'a'..=b'z')
+            .chain(b'A'..=b'Z')
             .map(|c| c as char)
-            .collect();
-
-        letters
+            .collect()
     };
 }
 
 pub const WHITESPACE_CHARS: [char; 2] = [' ', '\t'];
 
+/// Unicode byte order mark, that some editors put at the start of files
+pub const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
 /// Language reserved keywords
 pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "fn" => TokenType::Function,
@@ -31,55 +33,379 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "false" => TokenType::False,
     "if" => TokenType::If,
     "else" => TokenType::Else,
+    // Sugar for 'else if'
+    "elif" => TokenType::Elif,
     "return" => TokenType::Return,
+    // Word aliases of the logical operators
+    "and" => TokenType::And,
+    "or" => TokenType::Or,
+    "not" => TokenType::Bang,
 };
 
+/// Why a lexer couldn't be created.
+/// Problems in the source itself never end up here: they're reported
+/// as Illegal tokens while lexing. An empty source is fine too, it's
+/// just made of EOF.
+#[derive(Debug, Error)]
+pub enum LexerError {
+    /// The source couldn't be read, EG: it's not valid UTF-8.
+    #[error("Failed to read the input: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// IO errors can't be cloned, so the clone keeps their kind and message only.
+impl Clone for LexerError {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Io(e) => Self::Io(std::io::Error::new(e.kind(), e.to_string())),
+        }
+    }
+}
+
+/// IO errors are compared by kind and message, as they can't be compared as they are.
+impl PartialEq for LexerError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Io(a), Self::Io(b)) => a.kind() == b.kind() && a.to_string() == b.to_string(),
+        }
+    }
+}
+
+/// Options that change how the lexer tokenizes its input.
+#[derive(Debug, Clone)]
+pub struct LexerConfig {
+    /// Emit Indent/Dedent tokens whenever the leading whitespace of a line
+    /// grows or shrinks, so that blocks can be delimited Python-style
+    /// instead of with braces.
+    /// Indentation must be consistent: either only spaces or only tabs
+    /// throughout the whole input, and a dedent must go back to the width
+    /// of an enclosing block. Anything else produces an Illegal token.
+    /// Blank lines never change the indentation level.
+    pub indentation_blocks: bool,
+    /// Emit a NewLine token at the end of every line.
+    /// When disabled, newlines are skipped like any other whitespace, for
+    /// embedders that rely on spans rather than tokens to track lines.
+    pub emit_newlines: bool,
+    /// What starts a comment running to the end of the line, EG: '//' or '#'.
+    /// A shebang on the first line is skipped even if it starts with it.
+    /// An empty prefix disables comments.
+    pub comment_prefix: String,
+    /// Emit a Comment token for each comment, instead of skipping it,
+    /// for tools that need to print comments back (EG: the formatter).
+    pub emit_comments: bool,
+}
+
+impl Default for LexerConfig {
+    fn default() -> Self {
+        LexerConfig {
+            indentation_blocks: false,
+            emit_newlines: true,
+            comment_prefix: "//".to_owned(),
+            emit_comments: false,
+        }
+    }
+}
+
+/// Decides which numeric literals the lexer accepts, so that embedders
+/// can support extra formats (EG: octal '0o777' or unit suffixes like '5px')
+/// without touching the lexer itself.
+/// Note that identifiers are checked first, so a number can't start with
+/// a letter or '_'.
+pub trait NumberLexer {
+    /// Given the source text starting at the current char, return how many
+    /// chars make up the number literal found there, if any.
+    fn scan(&self, source: &str) -> Option<usize>;
+}
+
+/// The default number format: plain decimal integers, EG: 123456
+pub struct DecimalNumberLexer;
+
+impl NumberLexer for DecimalNumberLexer {
+    fn scan(&self, source: &str) -> Option<usize> {
+        let num_digits = source.chars().take_while(|c| c.is_numeric()).count();
+        if num_digits > 0 {
+            Some(num_digits)
+        } else {
+            None
+        }
+    }
+}
+
 pub struct Lexer {
     /// Text to lex
     input: String,
-    /// Current position in ``input``, points to the current char
+    /// Current position in ``input``, points to the current char.
+    /// Positions are counted in ``char``s, not bytes, so a multi-byte
+    /// character (EG: an emoji) only ever advances them by one.
     position: usize,
     /// Current reading position in ``input``, after the current char
     read_position: usize,
     /// Current char under examination
-    pub r#char: char,
+    r#char: char,
+    /// Line of the current char, starting from 1
+    line: usize,
+    /// Position of the first char of the current line
+    line_start: usize,
+    config: LexerConfig,
+    number_lexer: Box<dyn NumberLexer>,
+    /// Tokens that have already been lexed but not handed out yet
+    pending_tokens: VecDeque<Token>,
+    /// The token returned by ``peek_token``, with the line we were on
+    /// before lexing it, so that peeking doesn't move ``line``
+    peeked: Option<(Token, usize)>,
+    /// Whether we're at the beginning of a line
+    at_line_start: bool,
+    /// Widths of the currently open indented blocks, innermost last
+    indent_stack: Vec<usize>,
+    /// The whitespace char used for indentation, once we've seen one
+    indent_char: Option<char>,
 }
 
 impl Lexer {
-    pub fn new(text: &str) -> eyre::Result<Lexer> {
-        let first_char = match text.chars().nth(0) {
-            Some(c) => c,
-            None => {
-                eyre::bail!("No character found in position '0' in given text: '{text}'");
-            }
-        };
+    pub fn new(text: &str) -> Result<Lexer, LexerError> {
+        Lexer::from_string(text.to_owned(), LexerConfig::default())
+    }
+
+    /// Create a new lexer with non-default options.
+    pub fn with_config(text: &str, config: LexerConfig) -> Result<Lexer, LexerError> {
+        Lexer::from_string(text.to_owned(), config)
+    }
 
-        Ok(Lexer {
-            input: text.to_owned(),
+    /// Create a new lexer from anything we can read from (files, pipes, etc.).
+    /// The whole source is read upfront, so IO errors are reported here
+    /// rather than while lexing.
+    pub fn from_reader(mut reader: impl std::io::Read) -> Result<Lexer, LexerError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Lexer::from_string(text, LexerConfig::default())
+    }
+
+    fn from_string(input: String, config: LexerConfig) -> Result<Lexer, LexerError> {
+        // Default to the ASCII NUL character, so that an empty input is just EOF
+        let first_char = input.chars().next().unwrap_or('\0');
+
+        let mut lexer = Lexer {
+            input,
             position: 0,
             read_position: 1,
             r#char: first_char,
-        })
+            line: 1,
+            line_start: 0,
+            config,
+            number_lexer: Box::new(DecimalNumberLexer),
+            pending_tokens: VecDeque::new(),
+            peeked: None,
+            at_line_start: true,
+            indent_stack: vec![0],
+            indent_char: None,
+        };
+        lexer.skip_preamble();
+
+        Ok(lexer)
+    }
+
+    /// Skip what some files carry before the actual code: a UTF-8 byte order
+    /// mark added by some editors, and a shebang line (EG: '#!/usr/bin/env vvlang').
+    /// Positions still refer to the original input.
+    fn skip_preamble(&mut self) {
+        if self.char == BYTE_ORDER_MARK {
+            self.read_char();
+        }
+
+        if self.char == '#' && self.peek_char() == Some('!') {
+            self.skip_to_line_end();
+        }
+    }
+
+    /// Replace the rules used to lex numeric literals.
+    pub fn with_number_lexer(mut self, number_lexer: impl NumberLexer + 'static) -> Lexer {
+        self.number_lexer = Box::new(number_lexer);
+        self
     }
 
+    /// Skip whitespace and comments, including line continuations: a backslash
+    /// right before a newline joins the two lines, so no NewLine token is emitted.
+    /// Newlines themselves are skipped too when they don't get a token.
     fn skip_whitspace(&mut self) {
-        while WHITESPACE_CHARS.contains(&self.char) {
+        loop {
+            match self.char {
+                c if WHITESPACE_CHARS.contains(&c) => self.read_char(),
+                // Stop right before the newline, so that it still gets its token
+                _ if !self.config.emit_comments && self.at_comment() => {
+                    self.skip_to_line_end();
+                }
+                '\n' if !self.config.emit_newlines => {
+                    self.at_line_start = true;
+                    self.read_char();
+                    // Leave the leading whitespace for ``read_indentation``
+                    if self.config.indentation_blocks {
+                        break;
+                    }
+                }
+                '\r' if !self.config.emit_newlines
+                    && self.input.chars().nth(self.read_position) == Some('\n') =>
+                {
+                    self.at_line_start = true;
+                    self.read_char();
+                    self.read_char();
+                    if self.config.indentation_blocks {
+                        break;
+                    }
+                }
+                '\\' => {
+                    let continuation_len = match self.peek_char() {
+                        // - Unix-style
+                        Some('\n') => 2,
+                        // - Windows-style
+                        Some('\r')
+                            if self.input.chars().nth(self.read_position + 1) == Some('\n') =>
+                        {
+                            3
+                        }
+                        None | Some(_) => break,
+                    };
+                    for _ in 0..continuation_len {
+                        self.read_char();
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Move right before the end of the current line, so that the newline
+    /// still gets its token.
+    fn skip_to_line_end(&mut self) {
+        while !['\n', '\r', '\0'].contains(&self.char) {
             self.read_char();
         }
     }
 
+    /// Whether a comment starts at the current char.
+    fn at_comment(&self) -> bool {
+        let prefix = &self.config.comment_prefix;
+        !prefix.is_empty()
+            && self
+                .input
+                .chars()
+                .skip(self.position)
+                .take(prefix.chars().count())
+                .eq(prefix.chars())
+    }
+
+    /// Return the next token without consuming it:
+    /// the following call to ``next_token`` returns the same token.
+    pub fn peek_token(&mut self) -> Token {
+        if let Some((token, _)) = &self.peeked {
+            return token.clone();
+        }
+
+        let line = self.line;
+        let token = self.lex_token();
+        self.peeked = Some((token.clone(), line));
+        token
+    }
+
     pub fn next_token(&mut self) -> Token {
-        self.skip_whitspace();
+        let token = match self.peeked.take() {
+            Some((token, _)) => token,
+            None => self.lex_token(),
+        };
+        log::trace!(
+            "{:?} {:?} at {}..{}",
+            token.r#type,
+            token.literal,
+            token.start,
+            token.end
+        );
+        token
+    }
+
+    /// Do the actual work of ``next_token``.
+    fn lex_token(&mut self) -> Token {
+        loop {
+            if let Some(token) = self.pending_tokens.pop_front() {
+                return token;
+            }
+
+            if self.config.indentation_blocks && self.at_line_start {
+                self.at_line_start = false;
+                self.read_indentation();
+                if let Some(token) = self.pending_tokens.pop_front() {
+                    return token;
+                }
+            }
+
+            self.skip_whitspace();
+
+            if self.config.indentation_blocks {
+                // We've skipped over a newline, so the indentation of the new
+                // line needs to be read first
+                if self.at_line_start {
+                    continue;
+                }
+
+                // Close any block that is still open when we reach the end,
+                // even if trailing whitespace or a comment comes before it
+                if self.char == '\0' && self.indent_stack.len() > 1 {
+                    self.indent_stack.pop();
+                    let dedent = Token::new(TokenType::Dedent, "");
+                    return self.locate(dedent, self.position);
+                }
+            }
+
+            let start = self.position;
+            // Reading a NewLine token moves to the next line
+            let (line, char_offset) = (self.line, start - self.line_start);
+            let token = self.read_token();
+            return token
+                .with_position(start, self.position)
+                .with_line(line, char_offset);
+        }
+    }
+
+    /// Set where ``token`` is, given that it starts at ``start`` on the
+    /// current line and ends right before the current char.
+    fn locate(&self, token: Token, start: usize) -> Token {
+        token
+            .with_position(start, self.position)
+            .with_line(self.line, start - self.line_start)
+    }
 
+    /// Read the token starting at the current char, leaving the cursor
+    /// right after it.
+    fn read_token(&mut self) -> Token {
         // Special cases first
+        // A comment running to the end of the line, EG: '// note'
+        if self.config.emit_comments && self.at_comment() {
+            let start = self.position;
+            self.skip_to_line_end();
+            let literal: String = self
+                .input
+                .chars()
+                .skip(start)
+                .take(self.position - start)
+                .collect();
+            return Token::new(TokenType::Comment, &literal);
+        }
+
         // A potential keyword or variable name
         if is_letter(self.char) {
             return self.read_identifier();
         }
 
         // Numbers
-        if self.char.is_numeric() {
-            return self.read_number();
+        let remaining_input = match self.input.char_indices().nth(self.position) {
+            Some((byte_offset, _)) => &self.input[byte_offset..],
+            None => "",
+        };
+        // An empty number would never move the lexer forward
+        if let Some(num_chars) = self
+            .number_lexer
+            .scan(remaining_input)
+            .filter(|&num_chars| num_chars > 0)
+        {
+            return self.read_number(num_chars);
         }
 
         let c = &self.char.to_string();
@@ -88,7 +414,7 @@ impl Lexer {
         let token = match self.char {
             ';' => Token::new(TokenType::Semicolon, c),
             '=' => match self.peek_char() {
-                Some(next_c) if next_c == '=' => {
+                Some('=') => {
                     self.read_char();
                     Token::new(TokenType::Eq, "==")
                 }
@@ -99,11 +425,25 @@ impl Lexer {
             ')' => Token::new(TokenType::RParen, c),
             '{' => Token::new(TokenType::LBrace, c),
             '}' => Token::new(TokenType::RBrace, c),
+            '[' => Token::new(TokenType::LBracket, c),
+            ']' => Token::new(TokenType::RBracket, c),
             // Operators
-            '+' => Token::new(TokenType::Plus, c),
-            '-' => Token::new(TokenType::Minus, c),
+            '+' => match self.peek_char() {
+                Some('=') => {
+                    self.read_char();
+                    Token::new(TokenType::PlusAssign, "+=")
+                }
+                None | Some(_) => Token::new(TokenType::Plus, c),
+            },
+            '-' => match self.peek_char() {
+                Some('=') => {
+                    self.read_char();
+                    Token::new(TokenType::MinusAssign, "-=")
+                }
+                None | Some(_) => Token::new(TokenType::Minus, c),
+            },
             '!' => match self.peek_char() {
-                Some(next_c) if next_c == '=' => {
+                Some('=') => {
                     self.read_char();
                     Token::new(TokenType::NotEq, "!=")
                 }
@@ -111,28 +451,107 @@ impl Lexer {
             },
             '<' => Token::new(TokenType::Lt, c),
             '>' => Token::new(TokenType::Gt, c),
-            '/' => Token::new(TokenType::Slash, c),
-            '*' => Token::new(TokenType::Asterisk, c),
+            '/' => match self.peek_char() {
+                Some('=') => {
+                    self.read_char();
+                    Token::new(TokenType::SlashAssign, "/=")
+                }
+                None | Some(_) => Token::new(TokenType::Slash, c),
+            },
+            '*' => match self.peek_char() {
+                Some('=') => {
+                    self.read_char();
+                    Token::new(TokenType::AsteriskAssign, "*=")
+                }
+                None | Some(_) => Token::new(TokenType::Asterisk, c),
+            },
+            '&' => match self.peek_char() {
+                Some('&') => {
+                    self.read_char();
+                    Token::new(TokenType::And, "&&")
+                }
+                None | Some(_) => Token::new(TokenType::Illegal, c),
+            },
+            '|' => match self.peek_char() {
+                Some('|') => {
+                    self.read_char();
+                    Token::new(TokenType::Or, "||")
+                }
+                None | Some(_) => Token::new(TokenType::Illegal, c),
+            },
             // Special
-            '\0' => Token::new(TokenType::EOF, ""),
+            // Don't move past the end, so that EOF has an empty span
+            '\0' => return Token::new(TokenType::EOF, ""),
             // Newlines
             // - Unix-style
             '\n' => Token::new(TokenType::NewLine, "\n"),
             // - Windows-style
             '\r' => match self.peek_char() {
-                Some(next_c) if next_c == '\n' => {
+                Some('\n') => {
                     self.read_char();
                     Token::new(TokenType::NewLine, "\r\n")
                 }
-                None | Some(_) => Token::new(TokenType::Illegal, c),
+                None | Some(_) => Token::new(TokenType::Illegal, &illegal_literal('\r')),
             },
-            _ => Token::new(TokenType::Illegal, c),
+            _ => Token::new(TokenType::Illegal, &illegal_literal(self.char)),
         };
 
+        if token.r#type == TokenType::NewLine {
+            self.at_line_start = true;
+        }
+
         self.read_char();
         token
     }
 
+    /// Read the leading whitespace of a line and queue the Indent/Dedent
+    /// tokens needed to go from the previous indentation level to this one.
+    fn read_indentation(&mut self) {
+        let line_start = self.position;
+        let mut indentation: Vec<char> = Vec::new();
+        while WHITESPACE_CHARS.contains(&self.char) {
+            indentation.push(self.char);
+            self.read_char();
+        }
+
+        // Blank lines don't open or close blocks
+        if ['\n', '\r', '\0'].contains(&self.char) {
+            return;
+        }
+
+        let literal: String = indentation.iter().collect();
+        let illegal = self.locate(Token::new(TokenType::Illegal, &literal), line_start);
+
+        if let Some(&first_char) = indentation.first() {
+            let indent_char = *self.indent_char.get_or_insert(first_char);
+            if indentation.iter().any(|&c| c != indent_char) {
+                self.pending_tokens.push_back(illegal);
+                return;
+            }
+        }
+
+        let width = indentation.len();
+        let current_width = *self.indent_stack.last().unwrap_or(&0);
+
+        if width > current_width {
+            self.indent_stack.push(width);
+            let indent = self.locate(Token::new(TokenType::Indent, &literal), line_start);
+            self.pending_tokens.push_back(indent);
+            return;
+        }
+
+        while width < *self.indent_stack.last().unwrap_or(&0) {
+            self.indent_stack.pop();
+            let dedent = self.locate(Token::new(TokenType::Dedent, ""), self.position);
+            self.pending_tokens.push_back(dedent);
+        }
+
+        // We must have landed exactly on the level of an enclosing block
+        if width != *self.indent_stack.last().unwrap_or(&0) {
+            self.pending_tokens.push_back(illegal);
+        }
+    }
+
     fn read_identifier(&mut self) -> Token {
         // Read all chars until we find a non letter
         let mut letters: Vec<char> = Vec::new();
@@ -150,35 +569,47 @@ impl Lexer {
             None => TokenType::Ident,
         };
 
-        let token = Token::new(token_type, &s);
-        token
+        Token::new(token_type, &s)
     }
 
-    pub fn read_number(&mut self) -> Token {
-        // Read all chars until we find a non number
+    /// Read a number literal made of the next ``num_chars`` chars.
+    /// Its value is parsed right away, and left unset if it overflows.
+    fn read_number(&mut self, num_chars: usize) -> Token {
         let mut digits: Vec<char> = Vec::new();
-        while self.char.is_numeric() {
+        for _ in 0..num_chars {
             digits.push(self.char);
             self.read_char();
         }
 
         let s: String = digits.iter().collect();
-        let token = Token::new(TokenType::Int, &s);
-        token
+        Token::new(TokenType::Int, &s).with_value(s.parse::<i64>().ok())
     }
 
-    pub fn read_char(&mut self) {
-        self.char = match self.input.chars().nth(self.read_position) {
-            Some(c) => c,
-            None => '\0', // ASCII NUL character
-        };
+    fn read_char(&mut self) {
+        if self.char == '\n' {
+            self.line += 1;
+            self.line_start = self.read_position;
+        }
+
+        // Default to the ASCII NUL character
+        self.char = self.input.chars().nth(self.read_position).unwrap_or('\0');
 
         self.position = self.read_position;
         self.read_position += 1;
     }
 
+    /// Line of the current char, starting from 1.
+    /// It's kept up to date whether or not NewLine tokens are emitted,
+    /// and peeking at the next token doesn't change it.
+    pub fn line(&self) -> usize {
+        match &self.peeked {
+            Some((_, line)) => *line,
+            None => self.line,
+        }
+    }
+
     /// Peek at the next character without moving the cursor
-    pub fn peek_char(&mut self) -> Option<char> {
+    fn peek_char(&mut self) -> Option<char> {
         self.input.chars().nth(self.read_position)
     }
 }
@@ -187,6 +618,17 @@ fn is_letter(c: char) -> bool {
     LETTERS.contains(&c)
 }
 
+/// The literal of an Illegal token made of ``c``: the char itself, or its
+/// code point if it's a control char (EG: '\u{0007}' for the bell), which
+/// would be invisible or garble the output in error messages.
+fn illegal_literal(c: char) -> String {
+    if c.is_control() {
+        format!("\\u{{{:04X}}}", c as u32)
+    } else {
+        c.to_string()
+    }
+}
+
 #[cfg(test)]
 #[path = "../tests/lexer.rs"]
 mod lexer_tests;
@@ -1,389 +1,440 @@
-use std::cell::RefCell;
-use std::fmt::Display;
+use std::str::FromStr;
 
-use color_eyre::eyre;
+use thiserror::Error;
 
-use crate::core::lexer::Lexer;
+use crate::core::ast;
+use crate::core::lexer::{Lexer, LexerError};
+use crate::core::span::{Span, Spanned};
 use crate::core::tokens::{Token, TokenType};
 
-mod ast {
-
-    use super::*;
-
-    #[derive(Debug, PartialEq, Clone)]
-    /// A 'let' assignment of the form:
-    /// let <identifier> = <expression>;
-    /// EG:
-    ///   let x = 5;
-    ///   let x = add(5 + 5);
-    pub struct LetStatement {
-        pub token: Token,
-        pub identifier: Identifier,
-        pub value: RefCell<Expression>,
-    }
-
-    /// A 'return' assignment of the form:
-    /// return <expression>;
-    /// EG:
-    ///   return 5;
-    ///   return add(5 + 5);
-    #[derive(Debug, PartialEq, Clone)]
-    pub struct ReturnStatement {
-        pub token: Token,
-        pub value: RefCell<Expression>,
-    }
-
-    /// Represents the binding of a variable.
-    #[derive(Debug, PartialEq, Clone)]
-    pub struct Identifier {
-        /// The name of the variable.
-        /// EG: let x = 10; -> 'x'
-        pub name: String,
-    }
-
-    /// A statement consisting of a single expression.
-    /// EG:
-    ///   5;
-    ///   x + 10;
-    #[derive(Debug, PartialEq, Clone)]
-    pub struct ExpressionStatement {
-        pub token: Token,
-        pub expression: Expression,
-    }
-
-    /// Anything that returns a value.
-    /// EG:
-    ///   5;
-    ///   2+2;
-    ///   add(1, 2);
-    #[derive(Debug, PartialEq, Clone)]
-    pub struct Expression {
-        // pub token: Token,
-        pub tokens: Vec<Token>,
-    }
-
-    impl Expression {
-        /// TODO: Compute the value that the expression should return ?
-        pub fn compute(&self) -> String {
-            todo!();
-        }
-
-        pub fn literal(&self) -> String {
-            let exp_literal = self
-                .tokens
-                .iter()
-                .filter(|&t| t.r#type != TokenType::Semicolon)
-                .map(|t| t.literal.clone())
-                .collect::<Vec<String>>()
-                .join(" ");
-
-            exp_literal
-        }
-    }
-
-    /// Using the jergon of the Book, a 'Statement' is basically a
-    /// single node of the Abtract Syntax Tree.
-    /// We support 3 main types of Statements:
-    /// A 'let' assignment, a 'return' statement and a simple Expression.
-    #[derive(Debug, Clone, PartialEq)]
-    pub enum Statement {
-        Assignment(LetStatement),
-        Return(ReturnStatement),
-        SingleExpression(ExpressionStatement),
-    }
-
-    impl Statement {
-        fn token_literal(&self) -> String {
-            match self {
-                Statement::Assignment(let_statement) => let_statement.token.literal.to_owned(),
-                Statement::Return(return_statement) => return_statement.token.literal.to_owned(),
-                Statement::SingleExpression(expression) => expression.token.literal.to_owned(),
-            }
-        }
-    }
-
-    impl Display for Statement {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            let s = match self {
-                Statement::Assignment(let_statement) => {
-                    let exp = &let_statement.clone().value.into_inner();
-                    format!("let {} = {};", self.token_literal(), &exp.literal())
-                }
-                Statement::Return(return_statement) => {
-                    let exp = &return_statement.clone().value.into_inner();
-                    format!("return {};", &exp.literal())
-                }
-                Statement::SingleExpression(_) => {
-                    //
-                    self.token_literal()
-                }
-            };
-
-            write!(f, "{s}")
-        }
-    }
-
-    pub struct Program {
-        pub statements: Vec<Statement>,
-    }
-
-    impl Program {
-        pub fn new() -> Program {
-            Program {
-                statements: Vec::new(),
-            }
-        }
+/// The different kinds of errors that the parser can report, together
+/// with the tokens involved, so that tools don't need to parse messages.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParserErrorKind {
+    /// The grammar requires one of the ``expected`` tokens, but we found
+    /// another one.
+    UnexpectedToken {
+        expected: Vec<TokenType>,
+        found: Token,
+    },
+    /// The input ended in the middle of something, EG: a 'let' statement.
+    UnexpectedEof { while_parsing: &'static str },
+    /// A token that can't start a statement.
+    UnsupportedToken { found: Token },
+    /// A keyword that is only valid as part of another statement, EG: 'else'.
+    MisplacedKeyword { found: Token },
+    /// A '(', '{' or '[' that is never closed.
+    UnclosedDelimiter { open: Token },
+    /// A closing delimiter of a different kind than the last opened one,
+    /// which ``expected`` would have closed.
+    MismatchedDelimiter { expected: TokenType, found: Token },
+    /// A ')', '}' or ']' that doesn't close anything.
+    UnmatchedDelimiter { found: Token },
+    /// The lexer couldn't be created, so there was nothing to parse.
+    Lexer(LexerError),
+}
 
-        // FIXME: what needs this?
-        fn token_literal(&self) -> String {
-            match self.statements.get(0) {
-                Some(statement) => statement.token_literal(),
-                None => String::new(),
-            }
+impl ParserErrorKind {
+    /// A short, stable identifier for the kind of error, for tools to match on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnexpectedToken { .. } => "unexpected-token",
+            Self::UnexpectedEof { .. } => "unexpected-eof",
+            Self::UnsupportedToken { .. } => "unsupported-token",
+            Self::MisplacedKeyword { .. } => "misplaced-keyword",
+            Self::UnclosedDelimiter { .. } => "unclosed-delimiter",
+            Self::MismatchedDelimiter { .. } => "mismatched-delimiter",
+            Self::UnmatchedDelimiter { .. } => "unmatched-delimiter",
+            Self::Lexer(_) => "lexer-error",
         }
     }
 }
 
+#[derive(Debug, Error)]
+#[error("{message}")]
 pub struct ParserError {
+    /// Boxed, as the tokens it holds would make every parsing result large.
+    pub kind: Box<ParserErrorKind>,
     pub message: String,
     pub line_num: usize,
+    /// Offset of the error from the start of its line, in chars (0-based).
     pub char_offset: usize,
+    /// How many chars the error covers from ``char_offset``, EG: the length
+    /// of the unexpected token. 0 when it points at the end of the input.
+    pub width: usize,
 }
 
 impl ParserError {
-    fn new(message: &str, line_num: usize, char_offset: usize) -> ParserError {
+    fn new(
+        kind: ParserErrorKind,
+        message: &str,
+        line_num: usize,
+        char_offset: usize,
+        width: usize,
+    ) -> ParserError {
         ParserError {
+            kind: Box::new(kind),
             message: message.to_owned(),
             line_num,
             char_offset,
+            width,
         }
     }
 }
 
+/// The most statements that we reserve room for before parsing,
+/// past that the program grows as needed.
+const MAX_STATEMENTS_HINT: usize = 1024;
+
 pub struct Parser {
     lexer: Lexer,
     current_token: Token,
     peek_token: Token,
+    /// Rough guess of how many statements the program has,
+    /// capped so that a huge input can't reserve a huge program upfront.
+    statements_hint: usize,
+    /// Opening delimiters not closed yet, innermost last.
+    open_delimiters: Vec<Token>,
     /// Errors that we encountered while parsing the program.
     pub errors: Vec<ParserError>,
 }
 
 impl Parser {
     /// Create a new parser from the given text.
-    pub fn new(text: &str) -> eyre::Result<Parser> {
+    pub fn new(text: &str) -> Result<Parser, LexerError> {
         let mut lexer = Lexer::new(text)?;
         let first_token = lexer.next_token();
         let second_token = lexer.next_token();
-        Ok(Parser {
+        let mut parser = Parser {
             lexer,
             current_token: first_token,
             peek_token: second_token,
+            // Most statements sit on their own line
+            statements_hint: text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .take(MAX_STATEMENTS_HINT)
+                .count(),
+            open_delimiters: Vec::new(),
             errors: Vec::new(),
-        })
+        };
+        parser.track_delimiter();
+        Ok(parser)
     }
 
-    pub fn report_errors(&self) {
-        if !self.errors.is_empty() {
-            let num_errors = self.errors.len();
-            eprintln!(
-                "\nFound {} error{} while parsing:",
-                num_errors,
-                if num_errors <= 1 { "" } else { "s" }
-            );
+    /// Read the next token
+    fn next_token(&mut self) {
+        self.current_token = self.peek_token.clone();
+        self.peek_token = self.lexer.next_token();
+        self.track_delimiter();
+    }
 
-            for error in self.errors.iter() {
-                eprint!("line {}; ", error.line_num);
-                eprintln!("{}", error.message);
+    /// Keep track of the delimiters opened and closed by the current token,
+    /// so that we can point at the opening one if it's never closed.
+    fn track_delimiter(&mut self) {
+        let token = &self.current_token;
+        match token.r#type {
+            TokenType::LParen | TokenType::LBrace | TokenType::LBracket => {
+                self.open_delimiters.push(token.clone());
             }
+            TokenType::RParen | TokenType::RBrace | TokenType::RBracket => {
+                let Some(open) = self.open_delimiters.pop() else {
+                    let message = format!("Unmatched '{}'", token.literal);
+                    let kind = ParserErrorKind::UnmatchedDelimiter {
+                        found: token.clone(),
+                    };
+                    let error = self.error_at(kind, &message, &self.current_token);
+                    self.errors.push(error);
+                    return;
+                };
+
+                let expected = closing_delimiter(&open.r#type);
+                if expected != token.r#type {
+                    // We still consider the opener closed, otherwise every
+                    // following closer would be reported as mismatched too.
+                    let message = format!(
+                        "Mismatched delimiter: expected '{expected}' to close '{}' at line {}, found '{}'",
+                        open.literal, open.line, token.literal
+                    );
+                    let kind = ParserErrorKind::MismatchedDelimiter {
+                        expected,
+                        found: token.clone(),
+                    };
+                    let error = self.error_at(kind, &message, &self.current_token);
+                    self.errors.push(error);
+                }
+            }
+            _ => {}
         }
     }
 
-    /// Read the next token
-    fn next_token(&mut self) {
-        self.current_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+    /// Report an error for each delimiter still open at the end of the input.
+    fn report_unclosed_delimiters(&mut self) {
+        for open in std::mem::take(&mut self.open_delimiters) {
+            let message = format!(
+                "Unclosed '{}' opened at line {}, col {}",
+                open.literal,
+                open.line,
+                open.char_offset + 1
+            );
+            let kind = ParserErrorKind::UnclosedDelimiter { open: open.clone() };
+            let error = self.error_at(kind, &message, &open);
+            self.errors.push(error);
+        }
     }
 
     /// Parse the text given in input (consuming it) and return
     /// the whole program.
     pub fn parse_program(&mut self) -> ast::Program {
-        let mut program = ast::Program::new();
-
-        let mut line_num = 1;
+        let mut program = ast::Program::with_capacity(self.statements_hint);
 
         loop {
-            // eprintln!("Current token: {:?}", self.current_token);
-            // eprintln!("Peek token: {:?}", self.peek_token);
-
-            // If there is nothing more to parse, exit
-            if self.peek_token.r#type == TokenType::EOF {
-                break;
-            }
-
-            let mut statement: Option<ast::Statement> = None;
-            match self.current_token.r#type {
-                // Newlines have no syntactical meaning, but are useful to keep
-                // track of where we are in the source code so that we can emit
-                // precise error messages.
-                TokenType::NewLine => {
-                    line_num += 1;
-                }
-                TokenType::Let => match self.parse_let_statement() {
-                    Ok(s) => {
-                        statement = Some(s);
-                    }
-                    Err(e) => {
-                        let error_message = format!("{e}");
-                        let error = ParserError::new(&error_message, line_num, 0);
-                        self.errors.push(error);
-                    }
-                },
-                TokenType::If => {
-                    statement = Some(self.parse_if_statement());
+            match self.parse_statement() {
+                Ok(Some(statement)) => {
+                    log::trace!(
+                        "Parsed statement at line {}: {statement}",
+                        self.current_token.line
+                    );
+                    program.push(statement);
                 }
-                TokenType::Return => match self.parse_return_statement() {
-                    Ok(s) => statement = Some(s),
-                    Err(e) => {
-                        let error_message = format!("{e}");
-                        let error = ParserError::new(&error_message, line_num, 0);
-                        self.errors.push(error);
+                Ok(None) => break,
+                Err(e) => {
+                    // EG: a stray ')' has already been reported as unmatched
+                    let already_reported = self.errors.last().is_some_and(|last| {
+                        (last.line_num, last.char_offset) == (e.line_num, e.char_offset)
+                    });
+                    if !already_reported {
+                        self.errors.push(e);
                     }
-                },
-                _ => {
-                    // FIXME: Test this out
-                    let error_message =
-                        format!("Unsupported token: '{}'", self.current_token.literal);
-                    let error = ParserError::new(&error_message, line_num, 0);
-                    self.errors.push(error);
-                }
-            };
-
-            match statement {
-                Some(s) => {
-                    let type_name = std::any::type_name_of_val(&s);
-                    eprintln!("Current statement: '{s}', type: {type_name}");
-                    program.statements.push(s);
                 }
-                None => {}
             }
-
-            self.next_token();
         }
 
+        self.report_unclosed_delimiters();
+
+        log::debug!(
+            "Parsed {} statement(s), found {} error(s)",
+            program.len(),
+            self.errors.len()
+        );
         program
     }
 
-    fn parse_if_statement(&mut self) -> ast::Statement {
-        todo!();
-    }
+    /// Parse the next statement, leaving the parser right after it,
+    /// even if it has errors.
+    /// Returns None once there is nothing more to parse.
+    pub fn parse_statement(&mut self) -> Result<Option<ast::Statement>, ParserError> {
+        // Newlines are accounted for when moving to the next token
+        while self.current_token.r#type == TokenType::NewLine {
+            self.next_token();
+        }
 
-    fn parse_let_statement(&mut self) -> eyre::Result<ast::Statement> {
-        // The next token should be the identifier name
-        // TODO: At some point I might need to implement a custom error type
-        if !self.next_token_is_of_type(TokenType::Ident) {
-            return Err(eyre::eyre!(
-                "Expected identifier, found '{}'",
-                self.peek_token.literal
-            ));
+        // If there is nothing more to parse, exit.
+        // A last token right before the end still gets parsed (and reported).
+        if self.current_token.r#type == TokenType::EOF {
+            return Ok(None);
         }
 
-        // Advance, so we can parse the identifier
-        self.next_token();
-        let identifier = ast::Identifier {
-            name: self.current_token.literal.to_owned(),
+        let statement = match self.current_token.r#type {
+            TokenType::Let => self.parse_let_statement(),
+            TokenType::If => self.parse_if_statement(),
+            TokenType::Return => self.parse_return_statement(),
+            TokenType::Ident if self.peek_token.r#type.is_assignment_op() => {
+                self.parse_assign_statement()
+            }
+            // These are only valid as part of an 'if' statement
+            TokenType::Else | TokenType::Elif => {
+                let error_message = format!(
+                    "'{}' can only follow the block of an 'if'",
+                    self.current_token.literal
+                );
+                let kind = ParserErrorKind::MisplacedKeyword {
+                    found: self.current_token.clone(),
+                };
+                Err(self.error_at(kind, &error_message, &self.current_token))
+            }
+            _ => {
+                // FIXME: Test this out
+                let error_message = format!("Unsupported token: '{}'", self.current_token.literal);
+                let kind = ParserErrorKind::UnsupportedToken {
+                    found: self.current_token.clone(),
+                };
+                Err(self.error_at(kind, &error_message, &self.current_token))
+            }
         };
 
-        let let_statement_token = self.current_token.clone();
-
-        // After the identifier there should be an '=' sign
-        if !self.next_token_is_of_type(TokenType::Assign) {
-            return Err(eyre::eyre!(
-                "Expected '=' operator, found {}",
-                self.peek_token.literal
-            ));
-        }
         self.next_token();
+        if statement.is_err() {
+            self.synchronize();
+        }
+        statement.map(Some)
+    }
 
-        // After the '=' there should be an expression
-        // FIXME: this is just a placeholder
-        let mut exp_literals: Vec<String> = vec![];
-
-        // For now, we consume everything until we reach a semicolon
-        // This means we're skipping expressions
-        while !self.current_token_is_of_type(TokenType::Semicolon) {
-            exp_literals.push(self.peek_token.literal.to_owned());
-            self.next_token();
-
-            if self.current_token_is_of_type(TokenType::EOF) {
-                return Err(eyre::eyre!("Expected ';', found end of file (EOF)"));
+    /// Skip the rest of a statement that failed to parse, up to its ';'
+    /// or to the start of the next statement, whichever comes first,
+    /// so that a single mistake isn't reported again token by token.
+    fn synchronize(&mut self) {
+        loop {
+            match self.current_token.r#type {
+                TokenType::Semicolon => {
+                    self.next_token();
+                    return;
+                }
+                TokenType::Let | TokenType::Return | TokenType::If | TokenType::EOF => return,
+                _ => self.next_token(),
             }
         }
+    }
 
-        let exp_literal = exp_literals
-            .iter()
-            .filter(|&s| s != ";")
-            .map(|s| s.clone())
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        let exp_token = Token {
-            r#type: TokenType::Illegal,
-            literal: exp_literal,
+    fn parse_if_statement(&mut self) -> Result<ast::Statement, ParserError> {
+        // TODO: parse conditionals once we can parse expressions properly
+        let kind = ParserErrorKind::UnsupportedToken {
+            found: self.current_token.clone(),
         };
+        Err(self.error_at(
+            kind,
+            "'if' statements are not supported yet",
+            &self.current_token,
+        ))
+    }
+
+    fn parse_let_statement(&mut self) -> Result<ast::Statement, ParserError> {
+        let let_statement_token = self.current_token.clone();
 
-        let expression = ast::Expression {
-            tokens: vec![exp_token],
+        // The next token should be the identifier name
+        let identifier_token = self.expect(TokenType::Ident)?;
+        let identifier = ast::Identifier {
+            name: identifier_token.literal.to_owned(),
         };
 
+        // After the identifier there should be an '=' sign
+        self.expect(TokenType::Assign)?;
+
+        // After the '=' there should be an expression
+        let expression = self.parse_expression_until_semicolon("a 'let' statement")?;
+
         let statement = ast::LetStatement {
             token: let_statement_token,
             identifier,
-            value: RefCell::new(expression),
+            value: expression,
         };
 
         Ok(ast::Statement::Assignment(statement))
     }
 
-    fn parse_return_statement(&mut self) -> eyre::Result<ast::Statement> {
-        // After the 'return' there should be an expression
-        // FIXME: this is just a placeholder
-        let mut exp_literals: Vec<String> = vec![];
+    fn parse_assign_statement(&mut self) -> Result<ast::Statement, ParserError> {
+        let identifier_token = self.current_token.clone();
+
+        // The current token is the identifier, and the next one the operator
+        self.next_token();
+        let operator_token = self.current_token.clone();
+
+        // After the operator there should be an expression
+        let expression = self.parse_expression_until_semicolon("an assignment")?;
 
-        // For now, we consume everything until we reach a semicolon
-        // This means we're skipping expressions
-        while !self.current_token_is_of_type(TokenType::Semicolon) {
-            exp_literals.push(self.peek_token.literal.to_owned());
+        let statement = desugar_compound_assign(&identifier_token, &operator_token, expression);
+        Ok(ast::Statement::Reassignment(statement))
+    }
+
+    fn parse_return_statement(&mut self) -> Result<ast::Statement, ParserError> {
+        let return_token = self.current_token.clone();
+
+        // A bare 'return;' returns null
+        if self.next_token_is_of_type(TokenType::Semicolon) {
+            self.next_token();
+            let statement = ast::ReturnStatement {
+                token: return_token,
+                value: Spanned::new(
+                    ast::Expression::null(),
+                    Span::empty_at(self.current_token.start),
+                ),
+            };
+            return Ok(ast::Statement::Return(statement));
+        }
+
+        // Otherwise there should be an expression
+        let expression = self.parse_expression_until_semicolon("a 'return' statement")?;
+
+        let statement = ast::ReturnStatement {
+            token: return_token,
+            value: expression,
+        };
+
+        Ok(ast::Statement::Return(statement))
+    }
+
+    /// Consume all the tokens up to the next ';' as an expression,
+    /// leaving the ';' as the current token.
+    /// ``while_parsing`` names the statement, for when the ';' is missing.
+    /// FIXME: this is just a placeholder, we're skipping real expression parsing
+    fn parse_expression_until_semicolon(
+        &mut self,
+        while_parsing: &'static str,
+    ) -> Result<Spanned<ast::Expression>, ParserError> {
+        let mut tokens: Vec<Token> = vec![];
+
+        while !self.next_token_is_of_type(TokenType::Semicolon) {
             self.next_token();
 
             if self.current_token_is_of_type(TokenType::EOF) {
-                return Err(eyre::eyre!("Expected ';', found end of file (EOF)"));
+                return Err(self.error_at(
+                    ParserErrorKind::UnexpectedEof { while_parsing },
+                    "Expected ';', found end of file (EOF)",
+                    &self.current_token,
+                ));
             }
+            tokens.push(self.current_token.clone());
         }
+        self.next_token();
 
-        let exp_literal = exp_literals
-            .iter()
-            .filter(|&s| s != ";")
-            .map(|s| s.clone())
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        let exp_token = Token {
-            r#type: TokenType::Illegal,
-            literal: exp_literal,
+        let span = match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => Span::new(first.start, last.end),
+            _ => Span::empty_at(self.current_token.start),
         };
 
-        let expression = ast::Expression {
-            tokens: vec![exp_token],
-        };
-        let statement = ast::ReturnStatement {
-            token: Token {
-                r#type: TokenType::Return,
-                literal: "return".to_owned(),
-            },
-            value: RefCell::new(expression),
+        Ok(Spanned::new(ast::Expression { tokens }, span))
+    }
+
+    /// Advance to the next token if it's of the given type and return it,
+    /// otherwise return an UnexpectedToken error (without advancing).
+    /// Like within expressions, newlines before the token are skipped,
+    /// so that a statement can be split over several lines.
+    fn expect(&mut self, t: TokenType) -> Result<Token, ParserError> {
+        while t != TokenType::NewLine && self.next_token_is_of_type(TokenType::NewLine) {
+            self.next_token();
+        }
+
+        if !self.next_token_is_of_type(t.clone()) {
+            return Err(self.unexpected_token(vec![t]));
+        }
+
+        self.next_token();
+        Ok(self.current_token.clone())
+    }
+
+    /// Build an UnexpectedToken error for the next token, with a message
+    /// derived from what we expected and what we found instead.
+    fn unexpected_token(&self, expected: Vec<TokenType>) -> ParserError {
+        let expected_message = expected
+            .iter()
+            .map(describe_token_type)
+            .collect::<Vec<String>>()
+            .join(" or ");
+        let error_message = format!(
+            "Expected {expected_message}, found {}",
+            describe_token(&self.peek_token)
+        );
+        let kind = ParserErrorKind::UnexpectedToken {
+            expected,
+            found: self.peek_token.clone(),
         };
+        self.error_at(kind, &error_message, &self.peek_token)
+    }
 
-        Ok(ast::Statement::Return(statement))
+    /// Build an error pointing at the given token.
+    fn error_at(&self, kind: ParserErrorKind, message: &str, token: &Token) -> ParserError {
+        let width = token.end.saturating_sub(token.start);
+        ParserError::new(kind, message, token.line, token.char_offset, width)
     }
 
     fn current_token_is_of_type(&self, t: TokenType) -> bool {
@@ -395,6 +446,103 @@ impl Parser {
     }
 }
 
+/// The delimiter that closes the given opening one
+fn closing_delimiter(t: &TokenType) -> TokenType {
+    match t {
+        TokenType::LParen => TokenType::RParen,
+        TokenType::LBrace => TokenType::RBrace,
+        TokenType::LBracket => TokenType::RBracket,
+        other => other.clone(),
+    }
+}
+
+/// How a token type is called in error messages.
+fn describe_token_type(t: &TokenType) -> String {
+    match t {
+        TokenType::Ident => "identifier".to_owned(),
+        TokenType::Int => "integer".to_owned(),
+        TokenType::EOF => "end of file (EOF)".to_owned(),
+        TokenType::NewLine => "end of line".to_owned(),
+        _ => format!("'{t}'"),
+    }
+}
+
+/// Turn an assignment with the given operator (EG: 'x += 1;') into a plain
+/// one (EG: 'x = x + (1);'), so that later stages only ever see '='.
+/// The value is wrapped in parentheses to keep its precedence, and the
+/// original operator is kept, see ``AssignStatement::written_value``.
+/// Plain assignments are returned as they are.
+pub fn desugar_compound_assign(
+    identifier: &Token,
+    operator: &Token,
+    value: Spanned<ast::Expression>,
+) -> ast::AssignStatement {
+    let value = match operator.r#type.compound_operator() {
+        None => value,
+        Some(binary_operator) => {
+            // The synthesized tokens point at the compound operator
+            let at_operator = |r#type: TokenType| Token {
+                literal: r#type.to_string(),
+                r#type,
+                ..operator.clone()
+            };
+
+            let mut tokens = vec![
+                identifier.clone(),
+                at_operator(binary_operator),
+                at_operator(TokenType::LParen),
+            ];
+            tokens.extend(value.node.tokens);
+            tokens.push(at_operator(TokenType::RParen));
+
+            let span = Span::new(identifier.start, value.span.end);
+            Spanned::new(ast::Expression { tokens }, span)
+        }
+    };
+
+    ast::AssignStatement {
+        token: identifier.clone(),
+        identifier: ast::Identifier {
+            name: identifier.literal.to_owned(),
+        },
+        operator: operator.clone(),
+        value,
+    }
+}
+
+/// How a token found in the source is called in error messages.
+fn describe_token(token: &Token) -> String {
+    match token.r#type {
+        TokenType::EOF | TokenType::NewLine => describe_token_type(&token.r#type),
+        _ => format!("'{}'", token.literal),
+    }
+}
+
+/// Parse a whole program in one go, EG:
+///   let program: Program = source.parse()?;
+/// All the errors found while parsing are returned at once.
+impl FromStr for ast::Program {
+    type Err = Vec<ParserError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = match Parser::new(s) {
+            Ok(p) => p,
+            Err(e) => {
+                let error_message = format!("{e}");
+                let error = ParserError::new(ParserErrorKind::Lexer(e), &error_message, 1, 0, 0);
+                return Err(vec![error]);
+            }
+        };
+
+        let program = parser.parse_program();
+        if parser.errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(parser.errors)
+        }
+    }
+}
+
 #[cfg(test)]
 #[path = "../tests/parser.rs"]
 mod parser_tests;
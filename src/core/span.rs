@@ -0,0 +1,61 @@
+use std::fmt::Display;
+use std::ops::Deref;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// A range of the source code, as the half-open range of chars [start, end).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    /// An empty span right at the given offset.
+    pub fn empty_at(offset: usize) -> Span {
+        Span::new(offset, offset)
+    }
+}
+
+/// Wraps a node of the AST together with the span of source it was parsed
+/// from, so that node structs don't need to carry their own positions.
+/// It derefs to the node, so it can be used pretty much like the node itself.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Spanned<T> {
+        Spanned { node, span }
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+/// Like tokens, nodes are compared regardless of where they are in the source.
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<T: Display> Display for Spanned<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.node.fmt(f)
+    }
+}
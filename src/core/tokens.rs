@@ -1,6 +1,12 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TokenType {
     Illegal, // Unknown token
     EOF,     // End of File
@@ -20,6 +26,13 @@ pub enum TokenType {
     Slash,
     Lt,
     Gt,
+    And,
+    Or,
+    // Compound assignments
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
 
     // Delimiters
     Comma,
@@ -28,6 +41,8 @@ pub enum TokenType {
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
 
     // Keywords
     Function,
@@ -36,56 +51,231 @@ pub enum TokenType {
     False,
     If,
     Else,
+    Elif,
     Return,
 
+    // Indentation-based blocks
+    Indent,
+    Dedent,
+
+    // Only emitted when asked for, see ``LexerConfig::emit_comments``
+    Comment,
+
     // No-ops
     NewLine,
 }
 
 impl Display for TokenType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s;
-        match self {
-            Self::Illegal => s = "Illegal",
-            Self::EOF => s = "EOF",
-            Self::Ident => s = "IDENT",
-            Self::Int => s = "int",
-            Self::Assign => s = "=",
-            Self::Comma => s = ",",
-            Self::Semicolon => s = ";",
-            Self::LParen => s = "(",
-            Self::RParen => s = ")",
-            Self::LBrace => s = "{",
-            Self::RBrace => s = "}",
+        let s = match self {
+            Self::Illegal => "Illegal",
+            Self::EOF => "EOF",
+            Self::Ident => "IDENT",
+            Self::Int => "int",
+            Self::Assign => "=",
+            Self::Comma => ",",
+            Self::Semicolon => ";",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::LBrace => "{",
+            Self::RBrace => "}",
+            Self::LBracket => "[",
+            Self::RBracket => "]",
             // Operators
-            Self::Eq => s = "==",
-            Self::NotEq => s = "!=",
-            Self::Plus => s = "+",
-            Self::Minus => s = "-",
-            Self::Slash => s = "/",
-            Self::Gt => s = ">",
-            Self::Lt => s = "<",
-            Self::Bang => s = "!",
-            Self::Asterisk => s = "*",
+            Self::Eq => "==",
+            Self::NotEq => "!=",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Slash => "/",
+            Self::Gt => ">",
+            Self::Lt => "<",
+            Self::Bang => "!",
+            Self::Asterisk => "*",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::PlusAssign => "+=",
+            Self::MinusAssign => "-=",
+            Self::AsteriskAssign => "*=",
+            Self::SlashAssign => "/=",
             // Keywords
-            Self::Function => s = "fn",
-            Self::Let => s = "let",
-            Self::True => s = "true",
-            Self::False => s = "false",
-            Self::If => s = "if",
-            Self::Else => s = "else",
-            Self::Return => s = "return",
+            Self::Function => "fn",
+            Self::Let => "let",
+            Self::True => "true",
+            Self::False => "false",
+            Self::If => "if",
+            Self::Else => "else",
+            Self::Elif => "elif",
+            Self::Return => "return",
+            // Indentation
+            Self::Indent => "INDENT",
+            Self::Dedent => "DEDENT",
+            Self::Comment => "COMMENT",
             // No-op
-            Self::NewLine => s = "\n",
-        }
+            Self::NewLine => "\n",
+        };
         write!(f, "{s}")
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+impl TokenType {
+    /// Whether the token assigns a value to a binding, EG: '=' or '+='.
+    pub fn is_assignment_op(&self) -> bool {
+        *self == TokenType::Assign || self.compound_operator().is_some()
+    }
+
+    /// How operators and delimiters are always spelled, EG: '==' for Eq.
+    /// None for the tokens whose text varies (EG: Ident or Int), for keywords,
+    /// and for the ones that aren't written as such (EG: EOF or Indent).
+    /// Unlike ``Display``, this is meant to be written back as code.
+    pub fn symbol(&self) -> Option<&'static str> {
+        let symbol = match self {
+            Self::Assign => "=",
+            Self::Eq => "==",
+            Self::NotEq => "!=",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Bang => "!",
+            Self::Asterisk => "*",
+            Self::Slash => "/",
+            Self::Lt => "<",
+            Self::Gt => ">",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::PlusAssign => "+=",
+            Self::MinusAssign => "-=",
+            Self::AsteriskAssign => "*=",
+            Self::SlashAssign => "/=",
+            Self::Comma => ",",
+            Self::Semicolon => ";",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::LBrace => "{",
+            Self::RBrace => "}",
+            Self::LBracket => "[",
+            Self::RBracket => "]",
+            Self::Illegal
+            | Self::EOF
+            | Self::Ident
+            | Self::Int
+            | Self::Function
+            | Self::Let
+            | Self::True
+            | Self::False
+            | Self::If
+            | Self::Else
+            | Self::Elif
+            | Self::Return
+            | Self::Indent
+            | Self::Dedent
+            | Self::Comment
+            | Self::NewLine => return None,
+        };
+        Some(symbol)
+    }
+
+    /// The operator applied by a compound assignment, EG: '+' for '+='.
+    pub fn compound_operator(&self) -> Option<TokenType> {
+        match self {
+            TokenType::PlusAssign => Some(TokenType::Plus),
+            TokenType::MinusAssign => Some(TokenType::Minus),
+            TokenType::AsteriskAssign => Some(TokenType::Asterisk),
+            TokenType::SlashAssign => Some(TokenType::Slash),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned when parsing the name of a token type that doesn't exist
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownTokenType(pub String);
+
+impl Display for UnknownTokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown token type '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTokenType {}
+
+/// Parse the name of a variant, as printed by ``Debug`` (EG: "Ident").
+impl FromStr for TokenType {
+    type Err = UnknownTokenType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let token_type = match s {
+            "Illegal" => Self::Illegal,
+            "EOF" => Self::EOF,
+            "Ident" => Self::Ident,
+            "Int" => Self::Int,
+            "Assign" => Self::Assign,
+            "Eq" => Self::Eq,
+            "NotEq" => Self::NotEq,
+            "Plus" => Self::Plus,
+            "Minus" => Self::Minus,
+            "Bang" => Self::Bang,
+            "Asterisk" => Self::Asterisk,
+            "Slash" => Self::Slash,
+            "Lt" => Self::Lt,
+            "Gt" => Self::Gt,
+            "And" => Self::And,
+            "Or" => Self::Or,
+            "PlusAssign" => Self::PlusAssign,
+            "MinusAssign" => Self::MinusAssign,
+            "AsteriskAssign" => Self::AsteriskAssign,
+            "SlashAssign" => Self::SlashAssign,
+            "Comma" => Self::Comma,
+            "Semicolon" => Self::Semicolon,
+            "LParen" => Self::LParen,
+            "RParen" => Self::RParen,
+            "LBrace" => Self::LBrace,
+            "RBrace" => Self::RBrace,
+            "LBracket" => Self::LBracket,
+            "RBracket" => Self::RBracket,
+            "Function" => Self::Function,
+            "Let" => Self::Let,
+            "True" => Self::True,
+            "False" => Self::False,
+            "If" => Self::If,
+            "Else" => Self::Else,
+            "Elif" => Self::Elif,
+            "Return" => Self::Return,
+            "Indent" => Self::Indent,
+            "Dedent" => Self::Dedent,
+            "Comment" => Self::Comment,
+            "NewLine" => Self::NewLine,
+            _ => return Err(UnknownTokenType(s.to_owned())),
+        };
+        Ok(token_type)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Token {
     pub r#type: TokenType,
     pub literal: String,
+    /// Offset of the first char of the token in the source
+    pub start: usize,
+    /// Offset right after the last char of the token in the source,
+    /// so that the token covers the half-open range [start, end).
+    /// Offsets are counted in chars, like the lexer does.
+    pub end: usize,
+    /// Line of the first char of the token, starting from 1.
+    /// The lexer counts every newline it goes through, so this is right
+    /// even when no NewLine token was emitted (EG: after a line continuation).
+    pub line: usize,
+    /// Offset of the first char of the token from the start of its line,
+    /// in chars and starting from 0.
+    pub char_offset: usize,
+    /// Value of an Int token, parsed once by the lexer so that consumers
+    /// don't need to parse the literal again.
+    /// None for every other token, and for integers that overflow an i64
+    /// or that aren't written in decimal.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub value: Option<i64>,
 }
 
 impl Token {
@@ -93,6 +283,43 @@ impl Token {
         Token {
             r#type,
             literal: literal.to_owned(),
+            start: 0,
+            end: 0,
+            line: 1,
+            char_offset: 0,
+            value: None,
         }
     }
+
+    /// Set the parsed value of an Int token.
+    pub fn with_value(mut self, value: Option<i64>) -> Token {
+        self.value = value;
+        self
+    }
+
+    /// Set where the token was found in the source.
+    pub fn with_position(mut self, start: usize, end: usize) -> Token {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Set the line the token was found on, and where in that line.
+    pub fn with_line(mut self, line: usize, char_offset: usize) -> Token {
+        self.line = line;
+        self.char_offset = char_offset;
+        self
+    }
 }
+
+/// Tokens are compared by type and literal only, regardless of where they
+/// appear in the source.
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        self.r#type == other.r#type && self.literal == other.literal
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/tokens.rs"]
+mod tokens_tests;
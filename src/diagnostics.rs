@@ -0,0 +1,272 @@
+//! Reporting of the errors found while processing a script, either as text
+//! for humans or as JSON for tools that can't (and shouldn't have to) parse
+//! our text messages.
+
+use std::ffi::OsStr;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::core::parser::ParserError;
+
+const RED: &str = "31";
+const BOLD_RED: &str = "1;31";
+const DIM: &str = "2";
+
+/// When to color the diagnostics
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ColorChoice {
+    /// Only when printing to a terminal, and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How to decorate the diagnostics printed as text
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    /// Use ANSI escape codes to color the output
+    pub color: bool,
+}
+
+impl Style {
+    pub fn new(color: bool) -> Style {
+        Style { color }
+    }
+
+    /// Wrap ``text`` in the given ANSI SGR code, if colors are enabled.
+    fn paint(&self, text: &str, code: &str) -> String {
+        if self.color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+/// Whether diagnostics should be colored, given the user's choice,
+/// whether stderr is a terminal and the value of the NO_COLOR variable.
+/// See https://no-color.org
+pub fn should_color(
+    choice: &ColorChoice,
+    stderr_is_terminal: bool,
+    no_color: Option<&OsStr>,
+) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => stderr_is_terminal && no_color.is_none_or(|value| value.is_empty()),
+    }
+}
+
+/// Decide whether to color diagnostics, looking at the actual environment.
+pub fn style_for(choice: &ColorChoice) -> Style {
+    use std::io::IsTerminal;
+
+    let no_color = std::env::var_os("NO_COLOR");
+    Style::new(should_color(
+        choice,
+        std::io::stderr().is_terminal(),
+        no_color.as_deref(),
+    ))
+}
+
+/// Render an error found in ``source`` as text, followed by the line
+/// it was found on and a caret pointing at it.
+/// ``source_name`` tells the user where the code came from (EG: a file name).
+pub fn render_error(error: &ParserError, source_name: &str, source: &str, style: Style) -> String {
+    let location = format!(
+        "{source_name}:{}:{}:",
+        error.line_num,
+        error.char_offset + 1
+    );
+    let mut rendered = format!(
+        "{} {}: {}\n",
+        style.paint(&location, DIM),
+        style.paint("error", BOLD_RED),
+        error.message
+    );
+
+    let Some(line) = source.lines().nth(error.line_num.saturating_sub(1)) else {
+        return rendered;
+    };
+
+    let padding = caret_padding(line, error.char_offset);
+    let line_num = error.line_num.to_string();
+    let gutter = " ".repeat(line_num.len());
+    rendered.push_str(&format!(" {line_num} | {line}\n"));
+    rendered.push_str(&format!(" {gutter} | {padding}{}\n", style.paint("^", RED)));
+    rendered
+}
+
+/// Render ``line`` followed by a caret under each of the given errors,
+/// as pairs of offset (in chars, from the start of the line) and message.
+pub fn render_line_errors(line: &str, errors: &[(usize, &str)], style: Style) -> String {
+    let mut rendered = format!("{line}\n");
+    for (char_offset, message) in errors.iter() {
+        rendered.push_str(&format!(
+            "{}{} {}: {message}\n",
+            caret_padding(line, *char_offset),
+            style.paint("^", RED),
+            style.paint("error", BOLD_RED),
+        ));
+    }
+    rendered
+}
+
+/// What to print before a caret for it to point at ``char_offset`` in
+/// ``line``. Tabs are kept, so that the caret lines up with the text.
+fn caret_padding(line: &str, char_offset: usize) -> String {
+    line.chars()
+        .chain(std::iter::repeat(' '))
+        .take(char_offset)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect()
+}
+
+/// Print the errors found while parsing ``source``, if any.
+pub fn report_errors(errors: &[ParserError], source_name: &str, source: &str, style: Style) {
+    if errors.is_empty() {
+        return;
+    }
+
+    let num_errors = errors.len();
+    eprintln!(
+        "\nFound {} error{} while parsing {source_name}:",
+        num_errors,
+        if num_errors <= 1 { "" } else { "s" }
+    );
+
+    for error in errors.iter() {
+        eprint!("{}", render_error(error, source_name, source, style));
+    }
+}
+
+/// A single error, as reported by `--format json`
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Diagnostic {
+    /// 1-based line of the error, if it comes from a specific place in the source
+    pub line: Option<usize>,
+    /// 1-based column of the error, if it comes from a specific place in the source
+    pub column: Option<usize>,
+    pub message: String,
+    /// A short, stable identifier for the kind of error
+    pub code: &'static str,
+}
+
+impl Diagnostic {
+    /// An error that prevented us from reading the source at all.
+    pub fn io_error(message: &str) -> Diagnostic {
+        Diagnostic {
+            line: None,
+            column: None,
+            message: message.to_owned(),
+            code: "io-error",
+        }
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(error: &ParserError) -> Diagnostic {
+        Diagnostic {
+            line: Some(error.line_num),
+            column: Some(error.char_offset + 1),
+            message: error.message.clone(),
+            code: error.kind.code(),
+        }
+    }
+}
+
+/// What `--diagnostics json` prints, for editors and other tools to rely on
+pub const DIAGNOSTICS_SCHEMA: &str = "\
+Each diagnostic is printed on its own line of stdout, as a JSON object with:
+  file          Path of the script, or '<stdin>'
+  start_line    1-based line where the diagnostic starts
+  start_column  1-based column where the diagnostic starts, counted in chars
+  end_line      1-based line where the diagnostic ends
+  end_column    1-based column right after the end of the diagnostic,
+                equal to start_column when it covers no text
+  code          A short, stable identifier of the kind of diagnostic,
+                EG: 'unexpected-token'
+  message       What went wrong, for humans
+The positions are null when the diagnostic isn't about a specific place
+in the script, EG: when it couldn't be read at all.
+All diagnostics are errors for now.
+Nothing else is printed on stdout.";
+
+/// A diagnostic tied to the file it was found in, as reported by
+/// `--diagnostics json`. See ``DIAGNOSTICS_SCHEMA``.
+/// Every diagnostic is an error for now.
+// TODO: add a severity once something (EG: a linter) can report warnings
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FileDiagnostic<'a> {
+    pub file: &'a str,
+    pub start_line: Option<usize>,
+    pub start_column: Option<usize>,
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl FileDiagnostic<'_> {
+    /// An error that prevented us from reading ``file`` at all.
+    pub fn io_error<'a>(file: &'a str, message: &str) -> FileDiagnostic<'a> {
+        FileDiagnostic {
+            file,
+            start_line: None,
+            start_column: None,
+            end_line: None,
+            end_column: None,
+            code: "io-error",
+            message: message.to_owned(),
+        }
+    }
+
+    /// An error found while parsing ``file``.
+    pub fn from_parser_error<'a>(file: &'a str, error: &ParserError) -> FileDiagnostic<'a> {
+        let start_column = error.char_offset + 1;
+        FileDiagnostic {
+            file,
+            start_line: Some(error.line_num),
+            start_column: Some(start_column),
+            // Errors never span more than a token, so they end on the same line
+            end_line: Some(error.line_num),
+            end_column: Some(start_column + error.width),
+            code: error.kind.code(),
+            message: error.message.clone(),
+        }
+    }
+}
+
+/// What we print when we fail to process a script
+#[cfg(feature = "serde")]
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    errors: &'a [Diagnostic],
+}
+
+/// Serialize ``value`` to JSON, indenting it when ``pretty`` is set.
+#[cfg(feature = "serde")]
+pub fn to_json(value: &impl Serialize, pretty: bool) -> String {
+    let result = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    // All of our types map to plain JSON objects with string keys
+    result.expect("Serializing to JSON should never fail")
+}
+
+/// Serialize the given errors to the JSON object we print on failure.
+#[cfg(feature = "serde")]
+pub fn errors_to_json(errors: &[Diagnostic], pretty: bool) -> String {
+    to_json(&Report { errors }, pretty)
+}
+
+#[cfg(test)]
+#[path = "tests/diagnostics.rs"]
+mod diagnostics_tests;
@@ -0,0 +1,111 @@
+//! Export of the parsed statements as a Graphviz graph, to see the tree
+//! built by the parser, EG:
+//!   vvlang --emit dot script.vv | dot -Tpng > ast.png
+
+use crate::core::ast::{Expression, Program, Statement};
+use crate::core::span::Spanned;
+use crate::core::tokens::TokenType;
+
+/// Accumulates the nodes and edges of the graph, naming nodes by their
+/// order of creation: 'n0', 'n1' and so on.
+#[derive(Default)]
+struct Graph {
+    lines: Vec<String>,
+    num_nodes: usize,
+}
+
+impl Graph {
+    /// Add a node with the given label, returning its index.
+    fn node(&mut self, label: &str) -> usize {
+        let index = self.num_nodes;
+        self.lines
+            .push(format!("    n{index} [label=\"{}\"];", escape_label(label)));
+        self.num_nodes += 1;
+        index
+    }
+
+    /// Add a child node to ``parent``, returning the index of the child.
+    fn child(&mut self, parent: usize, label: &str) -> usize {
+        let child = self.node(label);
+        self.lines.push(format!("    n{parent} -> n{child};"));
+        child
+    }
+
+    fn add_statement(&mut self, parent: usize, statement: &Statement) {
+        match statement {
+            Statement::Assignment(let_statement) => {
+                let node = self.child(parent, "Let");
+                self.child(
+                    node,
+                    &format!("Identifier: {}", let_statement.identifier.name),
+                );
+                self.add_expression(node, &let_statement.value);
+            }
+            Statement::Reassignment(assign_statement) => {
+                let node = self.child(parent, "Assign");
+                self.child(
+                    node,
+                    &format!("Identifier: {}", assign_statement.identifier.name),
+                );
+                self.add_expression(node, &assign_statement.value);
+            }
+            Statement::Return(return_statement) => {
+                let node = self.child(parent, "Return");
+                self.add_expression(node, &return_statement.value);
+            }
+            Statement::SingleExpression(expression_statement) => {
+                let node = self.child(parent, "Expression statement");
+                self.add_expression(node, &expression_statement.expression);
+            }
+        }
+    }
+
+    /// Expressions are still flat, so their tokens are their children.
+    fn add_expression(&mut self, parent: usize, expression: &Spanned<Expression>) {
+        if expression.node.is_null() {
+            self.child(parent, "Null");
+            return;
+        }
+
+        let node = self.child(parent, "Expression");
+        for token in expression.node.tokens.iter() {
+            if token.r#type != TokenType::NewLine {
+                self.child(node, &format!("{:?}: {}", token.r#type, token.literal));
+            }
+        }
+    }
+}
+
+/// Escape ``label`` so that it can be put between double quotes in DOT.
+fn escape_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render ``program`` as a Graphviz digraph, with a box for each node of
+/// the tree and an edge from each node to each of its children.
+pub fn program_to_dot(program: &Program) -> String {
+    let mut graph = Graph::default();
+    let root = graph.node("Program");
+    for statement in program.iter() {
+        graph.add_statement(root, statement);
+    }
+
+    format!(
+        "digraph ast {{\n    node [shape=box];\n{}\n}}\n",
+        graph.lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+#[path = "tests/dot.rs"]
+mod dot_tests;
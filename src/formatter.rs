@@ -0,0 +1,230 @@
+//! `vvlang fmt`: print scripts back in a canonical style.
+//! One statement per line, single spaces around binary operators,
+//! no space before ';' or ',', and a trailing newline.
+//! Comments are kept: a comment after a statement stays at the end of its
+//! line, any other one goes on its own line.
+//! A shebang line is kept as it is, and blank lines between statements are
+//! kept too, with runs of them collapsed into one.
+//! Formatting is idempotent: formatting formatted code changes nothing.
+
+use crate::core::ast::{Program, Statement};
+use crate::core::lexer::{Lexer, LexerConfig, BYTE_ORDER_MARK};
+use crate::core::parser::ParserError;
+use crate::core::tokens::{Token, TokenType};
+
+// TODO: indent blocks once the parser supports them.
+
+/// Parse ``source`` and print it back in the canonical style.
+pub fn format_source(source: &str) -> Result<String, Vec<ParserError>> {
+    let program: Program = source.parse()?;
+    Ok(format_program_with_comments(
+        &program,
+        &comments(source),
+        shebang(source),
+    ))
+}
+
+/// The shebang line at the start of ``source`` (EG: '#!/usr/bin/env vvlang'),
+/// if there's one. The lexer skips it, so it has to be put back verbatim.
+fn shebang(source: &str) -> Option<&str> {
+    let source = source.strip_prefix(BYTE_ORDER_MARK).unwrap_or(source);
+    if !source.starts_with("#!") {
+        return None;
+    }
+    source.lines().next()
+}
+
+/// The comments in ``source``, in order.
+fn comments(source: &str) -> Vec<Token> {
+    let config = LexerConfig {
+        emit_comments: true,
+        ..LexerConfig::default()
+    };
+    let Ok(mut lexer) = Lexer::with_config(source, config) else {
+        return Vec::new();
+    };
+
+    std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token)
+    })
+    .filter(|token| token.r#type == TokenType::Comment)
+    .collect()
+}
+
+/// Like ``format_program``, putting the given ``comments`` back in:
+/// a comment on the last line of a statement stays after it, and the ones
+/// before a statement (or in the middle of it) go on their own lines above it.
+/// The ``shebang`` goes first, and blank lines in the source are kept,
+/// one at most in a row.
+fn format_program_with_comments(
+    program: &Program,
+    comments: &[Token],
+    shebang: Option<&str>,
+) -> String {
+    let mut comments = comments.iter().peekable();
+    let mut formatted = String::new();
+    // The last source line of what we've printed so far
+    let mut previous_line = None;
+
+    if let Some(shebang) = shebang {
+        formatted.push_str(shebang);
+        formatted.push('\n');
+        previous_line = Some(1);
+    }
+
+    for (i, statement) in program.iter().enumerate() {
+        let start = statement.token().start;
+        let last_line = *statement.lines().end();
+        while let Some(comment) = comments.next_if(|c| c.start < start || c.line < last_line) {
+            push_blank_line(&mut formatted, previous_line, comment.line);
+            formatted.push_str(comment.literal.trim_end());
+            formatted.push('\n');
+            previous_line = Some(comment.line);
+        }
+
+        let first_line = *statement.lines().start();
+        // A comment in the middle of the statement was printed above it
+        if previous_line.is_none_or(|previous| previous < first_line) {
+            push_blank_line(&mut formatted, previous_line, first_line);
+        }
+        formatted.push_str(&format_statement(statement));
+        previous_line = Some(last_line);
+
+        // Unless the next statement is on the same line, before the comment
+        let next_start = program.get(i + 1).map(|next| next.token().start);
+        if let Some(comment) = comments
+            .next_if(|c| c.line == last_line && next_start.is_none_or(|next| c.start < next))
+        {
+            formatted.push(' ');
+            formatted.push_str(comment.literal.trim_end());
+        }
+        formatted.push('\n');
+    }
+
+    for comment in comments {
+        push_blank_line(&mut formatted, previous_line, comment.line);
+        formatted.push_str(comment.literal.trim_end());
+        formatted.push('\n');
+        previous_line = Some(comment.line);
+    }
+    formatted
+}
+
+/// Add a blank line before what starts at ``line``, if there's at least one
+/// between it and the ``previous_line`` we've printed in the source.
+fn push_blank_line(formatted: &mut String, previous_line: Option<usize>, line: usize) {
+    if previous_line.is_some_and(|previous| line > previous + 1) {
+        formatted.push('\n');
+    }
+}
+
+/// Print every statement of ``program`` on its own line.
+pub fn format_program(program: &Program) -> String {
+    program
+        .iter()
+        .map(|statement| format_statement(statement) + "\n")
+        .collect()
+}
+
+fn format_statement(statement: &Statement) -> String {
+    match statement {
+        Statement::Assignment(let_statement) => format!(
+            "let {} = {};",
+            let_statement.identifier.name,
+            format_expression(&let_statement.value.tokens)
+        ),
+        // Compound assignments are printed as written, not desugared
+        Statement::Reassignment(assign_statement) => format!(
+            "{} {} {};",
+            assign_statement.identifier.name,
+            assign_statement.operator.literal,
+            format_expression(assign_statement.written_value())
+        ),
+        Statement::Return(return_statement) if return_statement.value.is_null() => {
+            "return;".to_owned()
+        }
+        Statement::Return(return_statement) => format!(
+            "return {};",
+            format_expression(&return_statement.value.tokens)
+        ),
+        Statement::SingleExpression(expression_statement) => format!(
+            "{};",
+            format_expression(&expression_statement.expression.tokens)
+        ),
+    }
+}
+
+/// Join the tokens of an expression with canonical spacing,
+/// putting it all on one line.
+pub fn format_expression(tokens: &[Token]) -> String {
+    let tokens: Vec<Token> = tokens
+        .iter()
+        .filter(|token| token.r#type != TokenType::NewLine)
+        .cloned()
+        .collect();
+    let tokens = tokens.as_slice();
+
+    let mut formatted = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && needs_space_before(tokens, i) {
+            formatted.push(' ');
+        }
+        formatted.push_str(&token.literal);
+    }
+    formatted
+}
+
+/// Whether the token at ``i`` should be separated from the previous one.
+fn needs_space_before(tokens: &[Token], i: usize) -> bool {
+    let previous = &tokens[i - 1].r#type;
+    match (previous, &tokens[i].r#type) {
+        (_, TokenType::RParen | TokenType::RBracket | TokenType::Comma | TokenType::Semicolon) => {
+            false
+        }
+        (TokenType::LParen | TokenType::LBracket, _) => false,
+        // Calls and indexing, EG: add(1, 2) or items[0]
+        (
+            TokenType::Ident | TokenType::RParen | TokenType::RBracket,
+            TokenType::LParen | TokenType::LBracket,
+        ) => false,
+        // Unary operators stick to their operand, EG: -5 or !true
+        (TokenType::Minus | TokenType::Bang, _) if is_unary(tokens, i - 1) => false,
+        _ => true,
+    }
+}
+
+/// Whether the operator at ``i`` applies to what follows it only,
+/// because there's no operand before it.
+fn is_unary(tokens: &[Token], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    let previous = &tokens[i - 1].r#type;
+    is_binary_operator(previous)
+        || matches!(
+            previous,
+            TokenType::LParen | TokenType::LBracket | TokenType::Comma | TokenType::Bang
+        )
+}
+
+fn is_binary_operator(t: &TokenType) -> bool {
+    t.is_assignment_op()
+        || matches!(
+            t,
+            TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Asterisk
+                | TokenType::Slash
+                | TokenType::Eq
+                | TokenType::NotEq
+                | TokenType::Lt
+                | TokenType::Gt
+                | TokenType::And
+                | TokenType::Or
+        )
+}
+
+#[cfg(test)]
+#[path = "tests/formatter.rs"]
+mod formatter_tests;
@@ -0,0 +1,168 @@
+//! `vvlang highlight`: print scripts with their tokens colored,
+//! for terminals (ANSI escape codes) or for docs (HTML).
+//! The source is re-sliced by the spans of its tokens, so everything
+//! in between (whitespace, line continuations, etc.) is kept exactly.
+
+use crate::core::lexer::{Lexer, LexerConfig, KEYWORDS};
+use crate::core::tokens::{Token, TokenType};
+
+/// How to mark up the highlighted source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HighlightFormat {
+    /// ANSI escape codes, for terminals
+    Ansi,
+    /// <span>s inside a <pre>, to be styled with CSS
+    Html,
+}
+
+/// The kinds of token that get highlighted differently
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenClass {
+    Keyword,
+    Identifier,
+    Number,
+    Operator,
+    Punctuation,
+    Comment,
+    /// Anything the lexer couldn't make sense of
+    Illegal,
+}
+
+// TODO: add strings once the lexer supports them
+
+impl TokenClass {
+    /// The class of the <span> wrapping this kind of token in HTML
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            TokenClass::Keyword => "tok-keyword",
+            TokenClass::Identifier => "tok-identifier",
+            TokenClass::Number => "tok-number",
+            TokenClass::Operator => "tok-operator",
+            TokenClass::Punctuation => "tok-punctuation",
+            TokenClass::Comment => "tok-comment",
+            TokenClass::Illegal => "tok-illegal",
+        }
+    }
+
+    /// The SGR parameters used to color this kind of token in a terminal
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            TokenClass::Keyword => "1;35",
+            TokenClass::Identifier => "34",
+            TokenClass::Number => "33",
+            TokenClass::Operator => "36",
+            TokenClass::Punctuation => "2",
+            TokenClass::Comment => "3;90",
+            TokenClass::Illegal => "31",
+        }
+    }
+}
+
+/// How a token is highlighted, if at all.
+/// Newlines and indentation are left as they are.
+pub fn classify(token: &Token) -> Option<TokenClass> {
+    let class = match token.r#type {
+        TokenType::EOF | TokenType::NewLine | TokenType::Indent | TokenType::Dedent => return None,
+        TokenType::Illegal => TokenClass::Illegal,
+        TokenType::Ident => TokenClass::Identifier,
+        TokenType::Int => TokenClass::Number,
+        TokenType::Comment => TokenClass::Comment,
+        TokenType::Comma
+        | TokenType::Semicolon
+        | TokenType::LParen
+        | TokenType::RParen
+        | TokenType::LBrace
+        | TokenType::RBrace
+        | TokenType::LBracket
+        | TokenType::RBracket => TokenClass::Punctuation,
+        // Including 'and', 'or' and 'not', lexed as the operators they stand for
+        _ if KEYWORDS.contains_key(token.literal.as_str()) => TokenClass::Keyword,
+        _ => TokenClass::Operator,
+    };
+    Some(class)
+}
+
+/// Highlight every token of ``source`` in the given ``format``.
+pub fn highlight(source: &str, format: HighlightFormat) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let slice = |start: usize, end: usize| -> String { chars[start..end].iter().collect() };
+
+    let mut highlighted = String::new();
+    if format == HighlightFormat::Html {
+        highlighted.push_str("<pre class=\"vvlang\">");
+    }
+
+    // Comments are tokens too, so that they get their own color
+    let config = LexerConfig {
+        emit_comments: true,
+        ..LexerConfig::default()
+    };
+
+    // An empty script has no tokens
+    let mut end_of_last_token = 0;
+    if let Ok(mut lexer) = Lexer::with_config(source, config) {
+        loop {
+            let token = lexer.next_token();
+            if token.r#type == TokenType::EOF {
+                break;
+            }
+
+            // What's between tokens, EG: whitespace
+            let gap = slice(end_of_last_token, token.start.max(end_of_last_token));
+            push_text(&mut highlighted, &gap, None, format);
+
+            let text = slice(token.start, token.end);
+            push_text(&mut highlighted, &text, classify(&token), format);
+            end_of_last_token = token.end.max(end_of_last_token);
+        }
+    }
+    push_text(
+        &mut highlighted,
+        &slice(end_of_last_token, chars.len()),
+        None,
+        format,
+    );
+
+    if format == HighlightFormat::Html {
+        highlighted.push_str("</pre>\n");
+    }
+    highlighted
+}
+
+/// Append ``text`` to ``highlighted``, marked up as ``class`` if it has one.
+fn push_text(
+    highlighted: &mut String,
+    text: &str,
+    class: Option<TokenClass>,
+    format: HighlightFormat,
+) {
+    if text.is_empty() {
+        return;
+    }
+
+    match (format, class) {
+        (HighlightFormat::Ansi, None) => highlighted.push_str(text),
+        (HighlightFormat::Ansi, Some(class)) => {
+            highlighted.push_str(&format!("\x1b[{}m{text}\x1b[0m", class.ansi_code()));
+        }
+        (HighlightFormat::Html, None) => highlighted.push_str(&escape_html(text)),
+        (HighlightFormat::Html, Some(class)) => {
+            highlighted.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                class.css_class(),
+                escape_html(text)
+            ));
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+#[path = "tests/highlight.rs"]
+mod highlight_tests;
@@ -0,0 +1,22 @@
+//! Lexing and parsing of vvlang scripts, and the tools built on top of them:
+//! a formatter, a syntax highlighter and the reporting of diagnostics.
+//! The `vvz-lang` binary (the CLI and the REPL) only uses what's exported here.
+//!
+//! The most common types are re-exported at the root, EG:
+//!   let mut parser = vvlang::Parser::new("let x = 5;")?;
+//!   let program = parser.parse_program();
+
+pub mod core;
+pub mod diagnostics;
+pub mod dot;
+pub mod formatter;
+pub mod highlight;
+
+pub use crate::core::ast::{
+    AssignStatement, Expression, ExpressionStatement, Identifier, LetStatement, Program,
+    ReturnStatement, Statement,
+};
+pub use crate::core::lexer::{Lexer, LexerConfig, LexerError};
+pub use crate::core::parser::{Parser, ParserError, ParserErrorKind};
+pub use crate::core::span::{Span, Spanned};
+pub use crate::core::tokens::{Token, TokenType};
@@ -0,0 +1,45 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Prints every log record to stderr, prefixed by its level and
+/// the module it comes from.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Send the log records up to ``level`` to stderr.
+/// Only the first call has any effect.
+pub fn init(level: LevelFilter) {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// The level to log at, given how many times -v was passed and whether
+/// -q was: only warnings by default, phase summaries with -v, and every
+/// token and statement with -vv.
+pub fn level_for(verbose: u8, quiet: bool) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::Off,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/logging.rs"]
+mod logging_tests;
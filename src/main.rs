@@ -1,52 +1,435 @@
-#![feature(type_name_of_val)]
+use std::io::IsTerminal;
 
 use color_eyre::eyre;
 
-mod core;
+use vvlang::diagnostics::{self, Style};
+#[cfg(feature = "serde")]
+use vvlang::diagnostics::{Diagnostic, FileDiagnostic};
+use vvlang::highlight::{self, HighlightFormat};
+use vvlang::{dot, formatter, Lexer, Parser, TokenType};
 
-/// Start a REPL that prints back the result
-/// of tokenizing what the user has typed.
-fn repl() -> eyre::Result<()> {
-    eprintln!("Welcome to vvlang!");
+use crate::cli::{DiagnosticsFormat, ExitStatus, FormatAction, Input, Mode, OutputFormat};
+use crate::timings::Timings;
 
-    loop {
-        eprint!(">>> ");
+mod cli;
+mod logging;
+mod repl;
+mod timings;
+mod watch;
 
-        let mut user_input = String::new();
-        std::io::stdin().read_line(&mut user_input)?;
+/// How we refer to the source code when it's read from stdin
+const STDIN_SOURCE_NAME: &str = "<stdin>";
 
-        if &user_input == "exit()\n" {
-            eprintln!("Exiting..");
-            break;
+/// How to process the scripts given on the command line
+struct RunOptions {
+    mode: Mode,
+    format: OutputFormat,
+    style: Style,
+    /// Report how long each stage of the pipeline took
+    time: bool,
+}
+
+/// Process the script at the given path, reporting any error found.
+fn run_file(path: &str, options: &RunOptions) -> ExitStatus {
+    log::debug!("Reading '{path}'");
+    match std::fs::read_to_string(path) {
+        Ok(text) => run_source(&text, path, options),
+        Err(e) => {
+            report_io_error(&format!("Could not read '{path}': {e}"), &options.format);
+            ExitStatus::NoInput
         }
-        let mut lexer = core::lexer::Lexer::new(&user_input)?;
+    }
+}
 
-        loop {
-            let token = lexer.next_token();
-            println!("{token:?}");
+/// Read the whole script from stdin and process it.
+fn run_stdin(options: &RunOptions) -> ExitStatus {
+    match std::io::read_to_string(std::io::stdin()) {
+        Ok(text) => run_source(&text, STDIN_SOURCE_NAME, options),
+        Err(e) => {
+            report_io_error(&format!("Could not read from stdin: {e}"), &options.format);
+            ExitStatus::NoInput
+        }
+    }
+}
 
-            if token.r#type == core::tokens::TokenType::EOF {
-                break;
+/// Report an error that prevented us from reading the source code.
+fn report_io_error(message: &str, format: &OutputFormat) {
+    match format {
+        OutputFormat::Text => eprintln!("{message}"),
+        #[cfg(feature = "serde")]
+        OutputFormat::Json { pretty } => {
+            let errors = [Diagnostic::io_error(message)];
+            println!("{}", diagnostics::errors_to_json(&errors, *pretty));
+        }
+    }
+}
+
+/// Take the given source code through the pipeline, up to the stage
+/// required by the options, reporting any error found.
+/// ``source_name`` is used to tell the user where errors come from.
+fn run_source(text: &str, source_name: &str, options: &RunOptions) -> ExitStatus {
+    let mut timings = Timings::new();
+    let status = process_source(text, source_name, options, &mut timings);
+
+    if options.time {
+        eprint!("{}", timings.report());
+    }
+    status
+}
+
+/// Do the actual work of ``run_source``, recording how long each stage takes.
+fn process_source(
+    text: &str,
+    source_name: &str,
+    options: &RunOptions,
+    timings: &mut Timings,
+) -> ExitStatus {
+    let format = &options.format;
+
+    if options.mode == Mode::Tokens {
+        let mut lexer = match Lexer::new(text) {
+            Ok(lexer) => lexer,
+            Err(e) => {
+                report_io_error(&format!("{source_name}: {e}"), format);
+                return ExitStatus::ParseError;
+            }
+        };
+
+        let tokens = timings.measure(
+            "lex",
+            "tokens",
+            || {
+                let mut tokens = Vec::new();
+                loop {
+                    let token = lexer.next_token();
+                    let is_eof = token.r#type == TokenType::EOF;
+                    tokens.push(token);
+                    if is_eof {
+                        return tokens;
+                    }
+                }
+            },
+            Vec::len,
+        );
+
+        match format {
+            OutputFormat::Text => {
+                for token in tokens.iter() {
+                    println!("{:?} {:?}", token.r#type, token.literal);
+                }
+            }
+            #[cfg(feature = "serde")]
+            OutputFormat::Json { pretty } => {
+                println!("{}", diagnostics::to_json(&tokens, *pretty));
             }
         }
+        return ExitStatus::Success;
+    }
+
+    // The parser lexes as it goes, so lexing is only timed on its own
+    // when the timings are going to be reported.
+    if options.time {
+        timings.measure("lex", "tokens", || timings::count_tokens(text), |n| *n);
     }
 
-    Ok(())
+    let mut parser = match Parser::new(text) {
+        Ok(parser) => parser,
+        Err(e) => {
+            report_io_error(&format!("{source_name}: {e}"), format);
+            return ExitStatus::ParseError;
+        }
+    };
+    let program = timings.measure(
+        "parse",
+        "statements",
+        || parser.parse_program(),
+        |program| program.len(),
+    );
+
+    match format {
+        OutputFormat::Text => {
+            diagnostics::report_errors(&parser.errors, source_name, text, options.style)
+        }
+        #[cfg(feature = "serde")]
+        OutputFormat::Json { pretty } if !parser.errors.is_empty() => {
+            let errors: Vec<Diagnostic> = parser.errors.iter().map(Diagnostic::from).collect();
+            println!("{}", diagnostics::errors_to_json(&errors, *pretty));
+        }
+        #[cfg(feature = "serde")]
+        OutputFormat::Json { .. } => {}
+    }
+
+    if !parser.errors.is_empty() {
+        return ExitStatus::ParseError;
+    }
+
+    if options.mode == Mode::Ast {
+        match format {
+            OutputFormat::Text => {
+                for statement in program.iter() {
+                    println!("{statement}");
+                }
+            }
+            #[cfg(feature = "serde")]
+            OutputFormat::Json { pretty } => {
+                println!("{}", diagnostics::to_json(&program, *pretty));
+            }
+        }
+    }
+
+    if options.mode == Mode::Dot {
+        print!("{}", dot::program_to_dot(&program));
+    }
+
+    ExitStatus::Success
 }
 
-fn main() -> eyre::Result<()> {
-    // repl()?;
-    use crate::core::parser::Parser;
-    let text = "
-    let something = 5;
-    return 10;
-    5;";
-
-    let mut parser = Parser::new(text)?;
+/// How we refer to ``input`` in messages.
+fn source_name(input: &Input) -> &str {
+    match input {
+        Input::File(path) => path,
+        Input::Stdin => STDIN_SOURCE_NAME,
+    }
+}
+
+/// Read the whole source code from ``input``, together with how we refer
+/// to it, or describe why it couldn't be read.
+fn read_source(input: &Input) -> Result<(String, &str), String> {
+    let text = match input {
+        Input::File(path) => {
+            std::fs::read_to_string(path).map_err(|e| format!("Could not read '{path}': {e}"))
+        }
+        Input::Stdin => std::io::read_to_string(std::io::stdin())
+            .map_err(|e| format!("Could not read from stdin: {e}")),
+    }?;
+    Ok((text, source_name(input)))
+}
+
+/// Read the whole source code from ``input``, together with how we refer
+/// to it, reporting any error found.
+fn read_input(input: &Input) -> Option<(String, &str)> {
+    read_source(input)
+        .map_err(|message| eprintln!("{message}"))
+        .ok()
+}
+
+/// Parse the source code read from ``input``, without running it,
+/// and report any error found in the given ``format``.
+/// Returns the number of errors found, or None if the input couldn't be read.
+fn check_input(input: &Input, style: Style, format: DiagnosticsFormat) -> Option<usize> {
+    // The source name only goes into JSON diagnostics
+    #[cfg_attr(not(feature = "serde"), allow(unused_variables))]
+    let report_io_error = |source_name: &str, message: &str| match format {
+        DiagnosticsFormat::Text => eprintln!("{message}"),
+        #[cfg(feature = "serde")]
+        DiagnosticsFormat::Json => {
+            let diagnostic = FileDiagnostic::io_error(source_name, message);
+            println!("{}", diagnostics::to_json(&diagnostic, false));
+        }
+    };
+
+    let (text, source_name) = match read_source(input) {
+        Ok(source) => source,
+        Err(message) => {
+            report_io_error(source_name(input), &message);
+            return None;
+        }
+    };
+
+    let mut parser = match Parser::new(&text) {
+        Ok(parser) => parser,
+        Err(e) => {
+            report_io_error(source_name, &format!("{source_name}: {e}"));
+            return Some(1);
+        }
+    };
     parser.parse_program();
-    parser.report_errors();
 
-    eprintln!("");
+    match format {
+        DiagnosticsFormat::Text => {
+            diagnostics::report_errors(&parser.errors, source_name, &text, style)
+        }
+        #[cfg(feature = "serde")]
+        DiagnosticsFormat::Json => {
+            for error in parser.errors.iter() {
+                let diagnostic = FileDiagnostic::from_parser_error(source_name, error);
+                println!("{}", diagnostics::to_json(&diagnostic, false));
+            }
+        }
+    }
+    Some(parser.errors.len())
+}
+
+/// Run each of the given inputs in order, stopping at the first one that fails.
+fn run_inputs(inputs: &[Input], options: &RunOptions) -> ExitStatus {
+    for input in inputs.iter() {
+        let status = match input {
+            Input::File(path) => run_file(path, options),
+            Input::Stdin => run_stdin(options),
+        };
+        if status != ExitStatus::Success {
+            return status;
+        }
+    }
+    ExitStatus::Success
+}
+
+/// Check all the given inputs, printing a summary at the end.
+fn check_inputs(inputs: &[Input], style: Style, format: DiagnosticsFormat) -> ExitStatus {
+    let results: Vec<Option<usize>> = inputs
+        .iter()
+        .map(|input| check_input(input, style, format))
+        .collect();
+    // An input we couldn't read counts as one error
+    let num_errors_per_input: Vec<usize> = results.iter().map(|n| n.unwrap_or(1)).collect();
+
+    let num_inputs = inputs.len();
+    let num_inputs_with_errors = num_errors_per_input.iter().filter(|&&n| n > 0).count();
+    let num_errors: usize = num_errors_per_input.iter().sum();
+    eprintln!(
+        "{num_inputs} file{} checked, {num_inputs_with_errors} with errors, {num_errors} error{} total",
+        if num_inputs == 1 { "" } else { "s" },
+        if num_errors == 1 { "" } else { "s" },
+    );
+
+    if results.contains(&None) {
+        ExitStatus::NoInput
+    } else if num_errors > 0 {
+        ExitStatus::ParseError
+    } else {
+        ExitStatus::Success
+    }
+}
+
+/// Format each of the given inputs in the canonical style, then print them,
+/// write them back or only report the ones that aren't formatted,
+/// depending on ``action``.
+fn format_inputs(inputs: &[Input], action: FormatAction, style: Style) -> ExitStatus {
+    let mut num_unreadable = 0;
+    let mut num_unwritable = 0;
+    let mut num_with_errors = 0;
+    let mut num_unformatted = 0;
+
+    for input in inputs {
+        let Some((text, source_name)) = read_input(input) else {
+            num_unreadable += 1;
+            continue;
+        };
+
+        let formatted = match formatter::format_source(&text) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                diagnostics::report_errors(&errors, source_name, &text, style);
+                num_with_errors += 1;
+                continue;
+            }
+        };
+
+        match action {
+            FormatAction::Print => print!("{formatted}"),
+            FormatAction::Write if formatted == text => {}
+            FormatAction::Write => match std::fs::write(source_name, &formatted) {
+                Ok(()) => eprintln!("Formatted {source_name}"),
+                Err(e) => {
+                    eprintln!("Could not write '{source_name}': {e}");
+                    num_unwritable += 1;
+                }
+            },
+            FormatAction::Check if formatted == text => {}
+            FormatAction::Check => {
+                eprintln!("{source_name} is not formatted");
+                num_unformatted += 1;
+            }
+        }
+    }
+
+    if num_unreadable > 0 {
+        ExitStatus::NoInput
+    } else if num_unwritable > 0 {
+        ExitStatus::CantCreate
+    } else if num_with_errors > 0 {
+        ExitStatus::ParseError
+    } else if num_unformatted > 0 {
+        ExitStatus::Unformatted
+    } else {
+        ExitStatus::Success
+    }
+}
+
+/// Print each of the given inputs with its tokens colored.
+fn highlight_inputs(inputs: &[Input], format: HighlightFormat) -> ExitStatus {
+    let mut status = ExitStatus::Success;
+    for input in inputs {
+        match read_input(input) {
+            Some((text, _)) => print!("{}", highlight::highlight(&text, format)),
+            None => status = ExitStatus::NoInput,
+        }
+    }
+    status
+}
+
+fn main() -> eyre::Result<()> {
+    let args = match cli::Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}\n\n{}", cli::USAGE);
+            ExitStatus::Usage.exit();
+        }
+    };
+
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    if args.version {
+        println!("{}", cli::version(args.verbose > 0));
+        return Ok(());
+    }
+
+    logging::init(logging::level_for(args.verbose, args.quiet));
+
+    let style = diagnostics::style_for(&args.color);
+    let options = RunOptions {
+        mode: args.mode.clone().unwrap_or(Mode::Run),
+        format: args.format.clone(),
+        style,
+        time: args.time,
+    };
+    let inputs_or_stdin = if args.inputs.is_empty() {
+        vec![Input::Stdin]
+    } else {
+        args.inputs.clone()
+    };
+    match options.mode {
+        Mode::Format(action) => format_inputs(&inputs_or_stdin, action, style).exit(),
+        Mode::Highlight(format) => highlight_inputs(&inputs_or_stdin, format).exit(),
+        _ => {}
+    }
+
+    if args.watch {
+        return watch::watch(&watch::files_to_watch(&args.inputs), || {
+            if options.mode == Mode::Check {
+                check_inputs(&args.inputs, style, args.diagnostics);
+            } else {
+                run_inputs(&args.inputs, &options);
+            }
+        });
+    }
+
+    if options.mode == Mode::Check {
+        check_inputs(&inputs_or_stdin, style, args.diagnostics).exit();
+    }
+
+    if args.inputs.is_empty() {
+        // Code is being piped in, or the user asked for a specific
+        // stage, so we're not going to be interactive
+        if args.mode.is_none() && std::io::stdin().is_terminal() {
+            return repl::run(!args.no_history, !args.no_rc, style);
+        }
+        run_stdin(&options).exit();
+    }
 
-    Ok(())
+    // Each script runs on its own
+    run_inputs(&args.inputs, &options).exit();
 }
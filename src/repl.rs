@@ -0,0 +1,772 @@
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use vvlang::core::ast::{Program, Statement};
+use vvlang::core::lexer::{Lexer, KEYWORDS, LETTERS};
+use vvlang::core::parser::{Parser, ParserError, ParserErrorKind};
+use vvlang::core::tokens::TokenType;
+use vvlang::diagnostics::{self, Style};
+
+use crate::cli;
+use crate::timings::{self, Timings};
+
+/// What the user can type to leave the REPL
+const EXIT_COMMANDS: [&str; 4] = ["exit()", "exit", "quit()", "quit"];
+
+/// Whether ``line`` asks to leave the REPL,
+/// regardless of surrounding whitespace and line endings.
+pub fn is_exit_command(line: &str) -> bool {
+    EXIT_COMMANDS.contains(&line.trim())
+}
+
+/// What the REPL should do after the user has typed a line
+#[derive(Debug, PartialEq)]
+pub enum Entry {
+    /// The input is complete and can be parsed
+    Complete(String),
+    /// Some delimiters are still open, so we wait for more lines
+    Incomplete,
+    /// The user gave up on the pending input
+    Abandoned,
+}
+
+/// Accumulates the lines typed by the user until they form a complete input,
+/// or one with errors that more lines can't fix.
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+    pending: String,
+}
+
+impl InputBuffer {
+    pub fn new() -> InputBuffer {
+        InputBuffer::default()
+    }
+
+    /// Whether we're in the middle of a multi-line input.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Add a line (including its line ending) to the input.
+    /// An empty line abandons any pending input.
+    /// An input with errors is still complete, so that they get reported.
+    pub fn push_line(&mut self, line: &str) -> Entry {
+        if self.is_pending() && line.trim().is_empty() {
+            self.pending.clear();
+            return Entry::Abandoned;
+        }
+
+        self.pending.push_str(line);
+        if classify_input(&self.pending) == Completeness::Incomplete {
+            return Entry::Incomplete;
+        }
+
+        Entry::Complete(std::mem::take(&mut self.pending))
+    }
+}
+
+/// Whether an input typed in the REPL is ready to be parsed
+#[derive(Debug, PartialEq)]
+pub enum Completeness {
+    /// It parses without errors
+    Complete,
+    /// It ends in the middle of a statement, or with delimiters still open,
+    /// so more lines could make it valid, EG: 'let x ='
+    Incomplete,
+    /// It has errors that more lines can't fix, EG: 'let 5 = x;'
+    Invalid,
+}
+
+/// Parse ``source`` to tell whether it's worth waiting for more lines:
+/// that's only the case when every error found is about the input ending
+/// too early, rather than about something being wrong in it.
+pub fn classify_input(source: &str) -> Completeness {
+    // More lines won't help the lexer, let parsing report what's wrong
+    let Ok(mut parser) = Parser::new(source) else {
+        return Completeness::Complete;
+    };
+    parser.parse_program();
+
+    // Running into the end of the input halfway through a statement
+    // means the rest of it may still be on the next lines
+    let ended_too_early = |error: &ParserError| match &*error.kind {
+        ParserErrorKind::UnexpectedEof { .. } | ParserErrorKind::UnclosedDelimiter { .. } => true,
+        ParserErrorKind::UnexpectedToken { found, .. } => found.r#type == TokenType::EOF,
+        _ => false,
+    };
+    if parser.errors.is_empty() {
+        Completeness::Complete
+    } else if parser.errors.iter().all(ended_too_early) {
+        Completeness::Incomplete
+    } else {
+        Completeness::Invalid
+    }
+}
+
+/// Text printed by the ':help' command
+const HELP: &str = "\
+Commands:
+  :help                Print this help
+  :tokens [on|off]     Toggle printing the tokens of each input
+  :tokens <code>       Print the tokens of <code>
+  :ast [on|off]        Toggle printing the parsed statements of each input
+  :ast <code>          Print the parsed statements of <code>
+  :time [on|off]       Toggle printing how long each stage takes for each input
+  :time <code>         Print how long each stage takes for <code>
+  :env                 List the current bindings
+  :reset               Forget the current bindings, keeping the inputs to :save
+  :reset hard          Forget the inputs accepted so far too
+  :paste               Take the next lines as they are, up to ':end' or Ctrl-D,
+                       and parse them as a single input
+  :save <path>         Write the inputs accepted so far to a script
+  :save! <path>        Same as :save, overwriting the file if it exists
+  :quit                Leave the REPL (same as 'exit' or 'quit')";
+
+/// Names of the commands listed in ':help', to complete them
+const COMMANDS: [&str; 10] = [
+    ":help", ":tokens", ":ast", ":time", ":env", ":reset", ":paste", ":save", ":save!", ":quit",
+];
+
+/// What the REPL can show about an input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum View {
+    Tokens,
+    Ast,
+    /// How long each stage of the pipeline took
+    Time,
+}
+
+/// What a line typed in the REPL asks for
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    /// Regular vvlang code
+    Code,
+    Help,
+    Quit,
+    Env,
+    /// Start over with no bindings, forgetting the transcript too if ``hard``
+    Reset {
+        hard: bool,
+    },
+    /// Collect the next lines verbatim, until ':end'
+    Paste,
+    /// Switch a view on or off for every input
+    Toggle(View),
+    Set(View, bool),
+    /// Show a view of the given code only
+    Show(View, String),
+    /// Write the accepted inputs to the given path
+    Save {
+        path: String,
+        overwrite: bool,
+    },
+    /// A command we don't know about, with its name
+    Unknown(String),
+}
+
+/// Map a line typed by the user to what it asks for.
+/// Only lines starting with ':' are commands, everything else is code.
+pub fn parse_action(line: &str) -> Action {
+    let line = line.trim();
+    if !line.starts_with(':') {
+        return Action::Code;
+    }
+
+    let (name, argument) = match line.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    };
+
+    let view = match name {
+        ":help" => return Action::Help,
+        ":quit" => return Action::Quit,
+        ":env" => return Action::Env,
+        ":paste" => return Action::Paste,
+        ":reset" => {
+            return match argument {
+                "" => Action::Reset { hard: false },
+                "hard" => Action::Reset { hard: true },
+                _ => Action::Unknown(line.to_owned()),
+            }
+        }
+        ":save" | ":save!" => {
+            return Action::Save {
+                path: argument.to_owned(),
+                overwrite: name == ":save!",
+            }
+        }
+        ":tokens" => View::Tokens,
+        ":ast" => View::Ast,
+        ":time" => View::Time,
+        _ => return Action::Unknown(name.to_owned()),
+    };
+
+    match argument {
+        "" => Action::Toggle(view),
+        "on" => Action::Set(view, true),
+        "off" => Action::Set(view, false),
+        code => Action::Show(view, code.to_owned()),
+    }
+}
+
+/// Whether the REPL should keep going after processing a line
+#[derive(Debug, PartialEq)]
+pub enum Flow {
+    Continue,
+    Exit,
+}
+
+/// The state of a REPL session, independent of how the lines are read.
+#[derive(Debug)]
+pub struct Session {
+    /// Print the tokens of each input
+    show_tokens: bool,
+    /// Print the parsed statements of each input
+    show_ast: bool,
+    /// Print how long each stage took for each input
+    show_time: bool,
+    buffer: InputBuffer,
+    /// The lines pasted so far, while in paste mode
+    paste: Option<String>,
+    /// The inputs that were parsed without errors, in order
+    transcript: Vec<String>,
+    /// Names bound by the inputs in the transcript, without duplicates
+    bindings: Vec<String>,
+    /// How to print diagnostics
+    style: Style,
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session {
+            show_tokens: false,
+            show_ast: true,
+            show_time: false,
+            buffer: InputBuffer::new(),
+            paste: None,
+            transcript: Vec::new(),
+            bindings: Vec::new(),
+            style: Style::default(),
+        }
+    }
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Print diagnostics with the given style.
+    pub fn with_style(mut self, style: Style) -> Session {
+        self.style = style;
+        self
+    }
+
+    /// The prompt to show before reading the next line.
+    pub fn prompt(&self) -> &'static str {
+        if self.is_pasting() {
+            "| "
+        } else if self.buffer.is_pending() {
+            "... "
+        } else {
+            ">>> "
+        }
+    }
+
+    /// Whether the given view is printed for each input.
+    pub fn is_shown(&self, view: View) -> bool {
+        match view {
+            View::Tokens => self.show_tokens,
+            View::Ast => self.show_ast,
+            View::Time => self.show_time,
+        }
+    }
+
+    /// Whether we're in paste mode, collecting lines until ':end'.
+    pub fn is_pasting(&self) -> bool {
+        self.paste.is_some()
+    }
+
+    /// Throw away any pending multi-line or pasted input.
+    pub fn cancel(&mut self) {
+        self.buffer = InputBuffer::new();
+        self.paste = None;
+    }
+
+    /// Leave paste mode, parsing what was pasted as a single input.
+    /// Does nothing when not in paste mode.
+    pub fn finish_paste(&mut self) -> eyre::Result<()> {
+        match self.paste.take() {
+            Some(source) if !source.trim().is_empty() => self.submit(source),
+            _ => Ok(()),
+        }
+    }
+
+    /// Process a line (including its line ending) typed by the user,
+    /// printing the result of parsing it once the input is complete.
+    /// An empty line, without even a line ending, means that we
+    /// reached the end of the input.
+    /// In paste mode, lines are only collected (even if they look like
+    /// commands) until ':end' or the end of the input.
+    pub fn process_line(&mut self, line: &str) -> eyre::Result<Flow> {
+        if let Some(paste) = &mut self.paste {
+            if line.is_empty() || line.trim() == ":end" {
+                self.finish_paste()?;
+            } else {
+                paste.push_str(line);
+            }
+            return Ok(Flow::Continue);
+        }
+
+        if line.is_empty() {
+            eprintln!("Exiting..");
+            return Ok(Flow::Exit);
+        }
+
+        // Inside a multi-line input, everything is code
+        if !self.buffer.is_pending() {
+            if is_exit_command(line) {
+                eprintln!("Exiting..");
+                return Ok(Flow::Exit);
+            }
+
+            match parse_action(line) {
+                Action::Code => {}
+                action => return self.run_action(action),
+            }
+        }
+
+        match self.buffer.push_line(line) {
+            Entry::Complete(source) => self.submit(source)?,
+            Entry::Incomplete | Entry::Abandoned => {}
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Parse a complete input, printing whatever the views ask for,
+    /// and keep it if it has no errors.
+    fn submit(&mut self, source: String) -> eyre::Result<()> {
+        if self.show_tokens {
+            print_tokens(&source)?;
+        }
+
+        if let Some(program) = self.run_code(&source, self.show_ast, self.show_time)? {
+            self.add_bindings(&program);
+            self.transcript.push(source);
+        }
+        Ok(())
+    }
+
+    fn run_action(&mut self, action: Action) -> eyre::Result<Flow> {
+        match action {
+            Action::Code => unreachable!("Code is not a command"),
+            Action::Help => eprintln!("{HELP}"),
+            Action::Quit => {
+                eprintln!("Exiting..");
+                return Ok(Flow::Exit);
+            }
+            // TODO: list the bindings once we can evaluate code
+            Action::Env => eprintln!("No bindings: evaluation is not supported yet"),
+            Action::Reset { hard } => {
+                let dropped = self.reset(hard);
+                let transcript = if hard { " and the inputs so far" } else { "" };
+                eprintln!("Dropped {dropped} binding(s){transcript}");
+            }
+            Action::Paste => {
+                eprintln!("Paste mode: type ':end' on its own line or Ctrl-D to finish");
+                self.paste = Some(String::new());
+            }
+            Action::Toggle(view) => self.set_shown(view, !self.is_shown(view)),
+            Action::Set(view, shown) => self.set_shown(view, shown),
+            Action::Show(View::Tokens, code) => print_tokens(&code)?,
+            Action::Show(View::Ast, code) => {
+                self.run_code(&code, true, false)?;
+            }
+            Action::Show(View::Time, code) => {
+                self.run_code(&code, self.show_ast, true)?;
+            }
+            Action::Save { path, .. } if path.is_empty() => {
+                eprintln!("Missing path to save to, EG: ':save session.vv'");
+            }
+            Action::Save { path, overwrite } => match self.save(Path::new(&path), overwrite) {
+                Ok(()) => eprintln!("Saved {} input(s) to '{path}'", self.transcript().len()),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    eprintln!("'{path}' already exists, use ':save!' to overwrite it");
+                }
+                Err(e) => eprintln!("Could not save to '{path}': {e}"),
+            },
+            Action::Unknown(name) => {
+                eprintln!("Unknown command '{name}', type :help to see the available ones");
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// The inputs that were parsed without errors so far, in order.
+    pub fn transcript(&self) -> &[String] {
+        &self.transcript
+    }
+
+    /// Names bound by the inputs that were parsed without errors so far.
+    pub fn bindings(&self) -> &[String] {
+        &self.bindings
+    }
+
+    /// Forget the bindings, as if the session had just started,
+    /// while keeping the settings and the history of the editor.
+    /// The transcript is kept for ':save' too, unless ``hard`` is set.
+    /// Returns how many bindings were dropped.
+    pub fn reset(&mut self, hard: bool) -> usize {
+        // TODO: replace the environment too once we can evaluate code
+        self.buffer = InputBuffer::new();
+        if hard {
+            self.transcript.clear();
+        }
+        std::mem::take(&mut self.bindings).len()
+    }
+
+    /// Run the script at ``path`` before the first prompt, so that what it
+    /// binds is available in the session.
+    /// A missing file is silently skipped, while any other problem is
+    /// reported without stopping the REPL from starting.
+    /// Returns whether the script was loaded.
+    pub fn load_rc(&mut self, path: &Path) -> bool {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return false,
+            Err(e) => {
+                eprintln!("Could not read '{}': {e}", path.display());
+                return false;
+            }
+        };
+
+        // Nothing to run, EG: a freshly created rc file
+        if source.trim().is_empty() {
+            return true;
+        }
+
+        match self.run_code(&source, false, false) {
+            // It's not an input of the session, so it's left out of the transcript
+            Ok(Some(program)) => {
+                self.add_bindings(&program);
+                true
+            }
+            Ok(None) => {
+                eprintln!("Skipped '{}' because of the errors above", path.display());
+                false
+            }
+            Err(e) => {
+                eprintln!("Could not load '{}': {e}", path.display());
+                false
+            }
+        }
+    }
+
+    fn add_bindings(&mut self, program: &Program) {
+        for statement in program.iter() {
+            if let Statement::Assignment(let_statement) = statement {
+                let name = &let_statement.identifier.name;
+                if !self.bindings.contains(name) {
+                    self.bindings.push(name.clone());
+                }
+            }
+        }
+    }
+
+    /// Write the transcript to ``path`` as a script.
+    /// Unless ``overwrite`` is set, fail if the file already exists.
+    pub fn save(&self, path: &Path, overwrite: bool) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true);
+        if overwrite {
+            options.create(true).truncate(true);
+        } else {
+            options.create_new(true);
+        }
+
+        let mut file = options.open(path)?;
+        file.write_all(self.transcript.concat().as_bytes())
+    }
+
+    /// Parse ``source``, printing the resulting statements if
+    /// ``print_statements`` is set, and how long it took if ``time`` is.
+    /// Returns the parsed program, unless errors were found.
+    fn run_code(
+        &self,
+        source: &str,
+        print_statements: bool,
+        time: bool,
+    ) -> eyre::Result<Option<Program>> {
+        let mut timings = Timings::new();
+        if time {
+            timings.measure("lex", "tokens", || timings::count_tokens(source), |n| *n);
+        }
+
+        let program = parse(source, print_statements, self.style, &mut timings)?;
+
+        if time {
+            eprint!("{}", timings.report());
+        }
+        Ok(program)
+    }
+
+    fn set_shown(&mut self, view: View, shown: bool) {
+        match view {
+            View::Tokens => self.show_tokens = shown,
+            View::Ast => self.show_ast = shown,
+            View::Time => self.show_time = shown,
+        }
+        let name = match view {
+            View::Tokens => "tokens",
+            View::Ast => "statements",
+            View::Time => "timings",
+        };
+        eprintln!("Printing {name}: {}", if shown { "on" } else { "off" });
+    }
+}
+
+/// Parse ``source``, reporting any error found, and print the resulting
+/// statements if ``print_statements`` is set.
+/// How long parsing takes is recorded in ``timings``.
+/// Returns the parsed program, unless errors were found.
+fn parse(
+    source: &str,
+    print_statements: bool,
+    style: Style,
+    timings: &mut Timings,
+) -> eyre::Result<Option<Program>> {
+    let mut parser = Parser::new(source)?;
+    let program = timings.measure(
+        "parse",
+        "statements",
+        || parser.parse_program(),
+        |program| program.len(),
+    );
+    report_errors(&parser.errors, source, style);
+
+    if !parser.errors.is_empty() {
+        return Ok(None);
+    }
+
+    if print_statements {
+        for statement in program.iter() {
+            println!("{statement}");
+        }
+    }
+
+    Ok(Some(program))
+}
+
+/// Print the errors found in the input typed by the user, if any,
+/// repeating each line that has errors with carets pointing at them.
+fn report_errors(errors: &[ParserError], source: &str, style: Style) {
+    let lines: Vec<&str> = source.lines().collect();
+    // Errors found at the end of the input can be past the last line
+    let last_line_num = errors.iter().map(|error| error.line_num).max().unwrap_or(0);
+
+    for line_num in 1..=last_line_num.max(lines.len()) {
+        let line = lines.get(line_num - 1).copied().unwrap_or("");
+        let line_errors: Vec<(usize, &str)> = errors
+            .iter()
+            .filter(|error| error.line_num == line_num)
+            .map(|error| (error.char_offset, error.message.as_str()))
+            .collect();
+
+        if !line_errors.is_empty() {
+            eprint!(
+                "{}",
+                diagnostics::render_line_errors(line, &line_errors, style)
+            );
+        }
+    }
+}
+
+/// Print every token found in ``source``, one per line.
+fn print_tokens(source: &str) -> eyre::Result<()> {
+    let mut lexer = Lexer::new(source)?;
+
+    loop {
+        let token = lexer.next_token();
+        println!("{token:?}");
+
+        if token.r#type == TokenType::EOF {
+            return Ok(());
+        }
+    }
+}
+
+/// Where the word that ends at ``position`` starts in ``line``.
+/// Both are byte offsets, as rustyline uses them.
+fn word_start(line: &str, position: usize) -> usize {
+    line[..position]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| LETTERS.contains(c))
+        .last()
+        .map_or(position, |(i, _)| i)
+}
+
+/// Suggest how to complete the word that ends at ``position`` in ``line``.
+/// Right after a ':' at the start of the line, that's the REPL commands,
+/// otherwise it's the keywords and the given ``bindings``.
+/// Nothing is suggested for an empty word, except for commands.
+/// The suggestions are whole words, sorted, meant to replace the one typed.
+pub fn complete(line: &str, position: usize, bindings: &[String]) -> Vec<String> {
+    let start = word_start(line, position);
+    let prefix = &line[start..position];
+
+    let mut candidates: Vec<String> = if line[..start].trim_start() == ":" {
+        COMMANDS
+            .iter()
+            .map(|command| &command[1..])
+            .filter(|name| name.starts_with(prefix))
+            .map(str::to_owned)
+            .collect()
+    } else if prefix.is_empty() {
+        Vec::new()
+    } else {
+        // TODO: add the builtins once there are any
+        KEYWORDS
+            .keys()
+            .copied()
+            .chain(bindings.iter().map(String::as_str))
+            .filter(|word| word.starts_with(prefix))
+            .map(str::to_owned)
+            .collect()
+    };
+
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Plugs ``complete`` into rustyline.
+#[derive(Default)]
+struct ReplHelper {
+    bindings: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok((word_start(line, pos), complete(line, pos, &self.bindings)))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Where the history of the REPL is kept, given the user's data directory.
+pub fn history_file(data_dir: Option<&Path>) -> Option<PathBuf> {
+    data_dir.map(|dir| dir.join("vvlang").join("history.txt"))
+}
+
+/// Where the script run at the start of the REPL is, given the value of
+/// $VVLANG_RC and the user's home directory.
+/// $VVLANG_RC wins when it's set, otherwise it's '~/.config/vvlang/init.vv'.
+pub fn rc_file(env_rc: Option<OsString>, home_dir: Option<&Path>) -> Option<PathBuf> {
+    match env_rc.filter(|path| !path.is_empty()) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => home_dir.map(|dir| dir.join(".config").join("vvlang").join("init.vv")),
+    }
+}
+
+/// Start a REPL that parses what the user has typed
+/// and prints back the resulting statements.
+/// Input spanning multiple lines is collected until all of its delimiters
+/// are closed, while an empty line or Ctrl-C throws it away.
+/// Lines starting with ':' are commands (see ':help'),
+/// and Tab completes keywords, bindings and commands,
+/// while 'exit' or 'quit' leave the REPL.
+/// Unless ``persist_history`` is false, the history is saved
+/// in the user's data directory between sessions.
+/// Unless ``load_rc`` is false, the rc file (see ``rc_file``) is run first.
+/// Diagnostics are printed with the given ``style``.
+pub fn run(persist_history: bool, load_rc: bool, style: Style) -> eyre::Result<()> {
+    eprintln!("{}", cli::banner());
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper::default()));
+    let history_path = if persist_history {
+        history_file(dirs::data_dir().as_deref())
+    } else {
+        None
+    };
+    if let Some(path) = &history_path {
+        // There's no history yet the first time the REPL is used
+        let _ = editor.load_history(path);
+    }
+
+    let mut session = Session::new().with_style(style);
+    if load_rc {
+        if let Some(path) = rc_file(std::env::var_os("VVLANG_RC"), dirs::home_dir().as_deref()) {
+            session.load_rc(&path);
+        }
+    }
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.bindings = session.bindings().to_vec();
+        }
+        match editor.readline(session.prompt()) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    editor.add_history_entry(line.as_str())?;
+                }
+                if session.process_line(&format!("{line}\n"))? == Flow::Exit {
+                    break;
+                }
+            }
+            // Ctrl-C
+            Err(ReadlineError::Interrupted) => session.cancel(),
+            // Ctrl-D
+            Err(ReadlineError::Eof) if session.is_pasting() => session.finish_paste()?,
+            Err(ReadlineError::Eof) => {
+                eprintln!("Exiting..");
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if let Some(path) = &history_path {
+        let saved = match path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir).map_err(ReadlineError::from),
+            None => Ok(()),
+        }
+        .and_then(|_| editor.save_history(path));
+
+        if let Err(e) = saved {
+            eprintln!("Could not save the history to '{}': {e}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "tests/repl.rs"]
+mod repl_tests;
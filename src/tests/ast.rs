@@ -0,0 +1,117 @@
+use crate::core::ast::{AssignStatement, Expression, Identifier, LetStatement, Program, Statement};
+use crate::core::span::{Span, Spanned};
+use crate::core::tokens::{Token, TokenType};
+
+use test_case::test_case;
+
+#[test]
+fn test_build_program_by_hand() {
+    let mut program = Program::new();
+    program.push(Statement::Assignment(LetStatement {
+        token: Token::new(TokenType::Let, "let"),
+        identifier: Identifier {
+            name: "x".to_owned(),
+        },
+        value: Spanned::new(
+            Expression {
+                tokens: vec![Token::new(TokenType::Int, "5")],
+            },
+            Span::new(8, 9),
+        ),
+    }));
+
+    let parsed: Program = "let x = 5;".parse().unwrap();
+    assert_eq!(program.statements(), parsed.statements());
+}
+
+/// A program with a let, an assignment and a return statement.
+fn three_statements() -> Program {
+    "let x = 5;\nx = 6;\nreturn x;".parse().unwrap()
+}
+
+#[test]
+fn test_len() {
+    assert_eq!(Program::new().len(), 0);
+    assert_eq!(three_statements().len(), 3);
+}
+
+#[test]
+fn test_is_empty() {
+    assert!(Program::new().is_empty());
+    assert!(!three_statements().is_empty());
+}
+
+#[test]
+fn test_get() {
+    assert_eq!(Program::new().get(0), None);
+
+    let program = three_statements();
+    assert!(matches!(program.get(1), Some(Statement::Reassignment(_))));
+    assert_eq!(program.get(3), None);
+}
+
+#[test]
+fn test_first() {
+    assert_eq!(Program::new().first(), None);
+    assert!(matches!(
+        three_statements().first(),
+        Some(Statement::Assignment(_))
+    ));
+}
+
+#[test]
+fn test_last() {
+    assert_eq!(Program::new().last(), None);
+    assert!(matches!(
+        three_statements().last(),
+        Some(Statement::Return(_))
+    ));
+}
+
+#[test]
+fn test_into_iter() {
+    assert_eq!((&Program::new()).into_iter().count(), 0);
+
+    let mut program = three_statements();
+    let lines: Vec<String> = (&program).into_iter().map(|s| s.to_string()).collect();
+    assert_eq!(lines, vec!["let x = 5;", "x = 6;", "return x;"]);
+
+    for statement in &mut program {
+        if let Statement::Reassignment(assign_statement) = statement {
+            assign_statement.identifier.name = "y".to_owned();
+        }
+    }
+    let owned: Vec<Statement> = program.into_iter().collect();
+    let Statement::Reassignment(assign_statement) = &owned[1] else {
+        panic!("Expected an assignment, found {:?}", owned[1]);
+    };
+    assert_eq!(assign_statement.identifier.name, "y");
+}
+
+#[test_case("let x = 1 +\n  2;", "let x = 1 + 2;"; "Let")]
+#[test_case("x = (1,\n 2\n);", "x = ( 1 , 2 );"; "Assignment")]
+#[test_case("return 1\n\n+ 2;", "return 1 + 2;"; "Return")]
+fn test_display_multi_line_statement(input: &str, expected: &str) {
+    let program: Program = input.parse().unwrap();
+    assert_eq!(program.statements()[0].to_string(), expected);
+}
+
+#[test_case(vec![]; "No tokens")]
+#[test_case(vec![Token::new(TokenType::Int, "1")]; "Not desugared")]
+fn test_written_value_built_by_hand(tokens: Vec<Token>) {
+    let statement = AssignStatement {
+        token: Token::new(TokenType::Ident, "x"),
+        identifier: Identifier {
+            name: "x".to_owned(),
+        },
+        operator: Token::new(TokenType::PlusAssign, "+="),
+        value: Spanned::new(
+            Expression {
+                tokens: tokens.clone(),
+            },
+            Span::new(5, 6),
+        ),
+    };
+
+    assert_eq!(statement.written_value(), tokens.as_slice());
+}
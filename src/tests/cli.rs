@@ -0,0 +1,193 @@
+use crate::cli::{
+    banner, version, Args, ColorChoice, DiagnosticsFormat, FormatAction, Input, Mode, OutputFormat,
+};
+use vvlang::highlight::HighlightFormat;
+
+use test_case::test_case;
+
+fn parse(args: &[&str]) -> Result<Args, String> {
+    Args::parse(args.iter().map(|s| s.to_string()))
+}
+
+#[test_case(&[], None, None; "No arguments")]
+#[test_case(&["a.vv"], None, Some(Input::File("a.vv".to_owned())); "Only a file")]
+#[test_case(&["--tokens", "a.vv"], Some(Mode::Tokens), Some(Input::File("a.vv".to_owned())); "Tokens")]
+#[test_case(&["a.vv", "--ast"], Some(Mode::Ast), Some(Input::File("a.vv".to_owned())); "Flags after the file")]
+#[test_case(&["--run", "-"], Some(Mode::Run), Some(Input::Stdin); "Explicit stdin")]
+#[test_case(&["--ast"], Some(Mode::Ast), None; "Flag without input")]
+#[test_case(&["--emit", "dot", "a.vv"], Some(Mode::Dot), Some(Input::File("a.vv".to_owned())); "Emit dot")]
+#[test_case(&["--emit=dot", "-"], Some(Mode::Dot), Some(Input::Stdin); "Emit dot with equals")]
+fn test_parse_args(args: &[&str], expected_mode: Option<Mode>, expected_input: Option<Input>) {
+    let parsed = parse(args).unwrap();
+    assert_eq!(parsed.mode, expected_mode);
+    assert_eq!(parsed.inputs.first(), expected_input.as_ref());
+}
+
+#[test_case(&["--check", "a.vv", "b.vv"], Some(Mode::Check); "Check")]
+#[test_case(&["a.vv", "b.vv"], None; "Run")]
+fn test_parse_args_multiple_files(args: &[&str], expected_mode: Option<Mode>) {
+    let parsed = parse(args).unwrap();
+    assert_eq!(parsed.mode, expected_mode);
+    assert_eq!(
+        parsed.inputs,
+        vec![
+            Input::File("a.vv".to_owned()),
+            Input::File("b.vv".to_owned())
+        ]
+    );
+}
+
+#[test_case(&["--tokens", "--ast", "a.vv"]; "Mutually exclusive modes")]
+#[test_case(&["--ast", "--format", "json", "a.vv", "b.vv"]; "JSON output of more than one file")]
+#[test_case(&["--frobnicate"]; "Unknown flag")]
+#[test_case(&["--run", "--format", "json"]; "JSON output when running")]
+#[test_case(&["--tokens", "--format", "yaml"]; "Unknown format")]
+#[test_case(&["--tokens", "--format"]; "Missing format")]
+#[test_case(&["--tokens", "--pretty"]; "Pretty without JSON")]
+#[test_case(&["--color", "sometimes"]; "Unknown color choice")]
+#[test_case(&["-v", "-q"]; "Verbose and quiet")]
+#[test_case(&["--watch"]; "Watch without files")]
+#[test_case(&["--watch", "a.vv", "-"]; "Watch stdin")]
+#[test_case(&["fmt", "--write", "--check", "a.vv"]; "Format write and check")]
+#[test_case(&["fmt", "--tokens", "a.vv"]; "Format with another mode")]
+#[test_case(&["fmt", "--write"]; "Format write without files")]
+#[test_case(&["fmt", "--write", "-"]; "Format write stdin")]
+#[test_case(&["fmt", "--watch", "a.vv"]; "Format watch")]
+#[test_case(&["highlight", "--format", "json", "a.vv"]; "Unknown highlight format")]
+#[test_case(&["highlight", "--ast", "a.vv"]; "Highlight with a mode")]
+#[test_case(&["--diagnostics", "json", "a.vv"]; "JSON diagnostics without check")]
+#[test_case(&["--emit", "svg", "a.vv"]; "Unknown emit format")]
+#[test_case(&["--emit"]; "Missing emit format")]
+#[test_case(&["--emit=dot", "--ast", "a.vv"]; "Emit with another mode")]
+#[test_case(&["--check", "--diagnostics", "xml", "a.vv"]; "Unknown diagnostics format")]
+fn test_parse_args_error(args: &[&str]) {
+    assert!(parse(args).is_err());
+}
+
+#[test_case(&["--tokens"], OutputFormat::Text; "Default format")]
+#[test_case(&["--ast", "--format", "text"], OutputFormat::Text; "Explicit text")]
+fn test_parse_args_format(args: &[&str], expected_format: OutputFormat) {
+    assert_eq!(parse(args).unwrap().format, expected_format);
+}
+
+#[cfg(feature = "serde")]
+#[test_case(&["--tokens", "--format", "json"], false; "JSON")]
+#[test_case(&["--pretty", "--ast", "--format", "json"], true; "Pretty JSON")]
+fn test_parse_args_json_format(args: &[&str], pretty: bool) {
+    assert_eq!(parse(args).unwrap().format, OutputFormat::Json { pretty });
+}
+
+#[test_case(&["--check"], DiagnosticsFormat::Text; "Default diagnostics")]
+#[test_case(&["--diagnostics", "text", "a.vv"], DiagnosticsFormat::Text; "Text diagnostics")]
+fn test_parse_args_diagnostics(args: &[&str], expected: DiagnosticsFormat) {
+    assert_eq!(parse(args).unwrap().diagnostics, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_parse_args_json_diagnostics() {
+    assert_eq!(
+        parse(&["--check", "--diagnostics", "json"])
+            .unwrap()
+            .diagnostics,
+        DiagnosticsFormat::Json
+    );
+}
+
+#[cfg(not(feature = "serde"))]
+#[test_case(&["--tokens", "--format", "json"]; "JSON output")]
+#[test_case(&["--check", "--diagnostics", "json"]; "JSON diagnostics")]
+fn test_parse_args_json_without_serde(args: &[&str]) {
+    assert_eq!(parse(args).unwrap_err(), super::JSON_NEEDS_SERDE);
+}
+
+#[test]
+fn test_parse_args_no_history() {
+    assert!(!parse(&[]).unwrap().no_history);
+    assert!(parse(&["--no-history"]).unwrap().no_history);
+}
+
+#[test]
+fn test_parse_args_no_rc() {
+    assert!(!parse(&[]).unwrap().no_rc);
+    assert!(parse(&["--no-rc"]).unwrap().no_rc);
+}
+
+#[test_case(&[], 0, false; "Default")]
+#[test_case(&["-v"], 1, false; "Verbose")]
+#[test_case(&["-vv"], 2, false; "Very verbose")]
+#[test_case(&["-v", "--verbose"], 2, false; "Repeated")]
+#[test_case(&["--quiet"], 0, true; "Quiet")]
+fn test_parse_args_verbosity(args: &[&str], expected_verbose: u8, expected_quiet: bool) {
+    let args = parse(args).unwrap();
+    assert_eq!(
+        (args.verbose, args.quiet),
+        (expected_verbose, expected_quiet)
+    );
+}
+
+#[test_case(&["--color", "always"], ColorChoice::Always; "Always")]
+#[test_case(&["--color", "never"], ColorChoice::Never; "Never")]
+#[test_case(&[], ColorChoice::Auto; "Default")]
+#[test_case(&["--no-color"], ColorChoice::Never; "No color")]
+#[test_case(&["--color", "always", "--no-color"], ColorChoice::Never; "Last one wins")]
+fn test_parse_args_color(args: &[&str], expected: ColorChoice) {
+    assert_eq!(parse(args).unwrap().color, expected);
+}
+
+#[test]
+fn test_version() {
+    assert_eq!(
+        version(false),
+        format!("vvlang {}", env!("CARGO_PKG_VERSION"))
+    );
+    assert!(parse(&["--version"]).unwrap().version);
+}
+
+#[test]
+fn test_version_verbose() {
+    let lines: Vec<String> = version(true).lines().map(str::to_owned).collect();
+
+    assert_eq!(lines[0], version(false));
+    // Anything we couldn't find out at build time is "unknown"
+    for (line, label) in lines[1..].iter().zip(["commit: ", "built: ", "target: "]) {
+        let value = line.strip_prefix(label).unwrap();
+        assert!(!value.is_empty(), "Empty value in '{line}'");
+    }
+    assert_eq!(lines.len(), 4);
+}
+
+#[test]
+fn test_banner() {
+    assert!(banner().contains(env!("CARGO_PKG_VERSION")));
+    assert!(banner().contains(env!("VVLANG_TARGET")));
+}
+
+#[test]
+fn test_parse_args_watch() {
+    assert!(!parse(&["a.vv"]).unwrap().watch);
+    assert!(parse(&["--watch", "a.vv", "b.vv"]).unwrap().watch);
+}
+
+#[test_case(&["fmt", "a.vv"], FormatAction::Print; "Print")]
+#[test_case(&["fmt", "--write", "a.vv"], FormatAction::Write; "Write")]
+#[test_case(&["fmt", "a.vv", "--check"], FormatAction::Check; "Check")]
+#[test_case(&["fmt", "--check", "--check", "a.vv"], FormatAction::Check; "Repeated")]
+fn test_parse_args_fmt(args: &[&str], expected: FormatAction) {
+    let args = parse(args).unwrap();
+    assert_eq!(args.mode, Some(Mode::Format(expected)));
+    assert_eq!(args.inputs, vec![Input::File("a.vv".to_owned())]);
+}
+
+#[test]
+fn test_parse_args_fmt_is_a_file_when_not_first() {
+    let args = parse(&["a.vv", "fmt"]).unwrap();
+    assert_eq!(args.mode, None);
+    assert_eq!(args.inputs.len(), 2);
+}
+
+#[test_case(&["highlight", "a.vv"], HighlightFormat::Ansi; "Default")]
+#[test_case(&["highlight", "--format", "html", "a.vv"], HighlightFormat::Html; "HTML")]
+fn test_parse_args_highlight(args: &[&str], expected: HighlightFormat) {
+    assert_eq!(parse(args).unwrap().mode, Some(Mode::Highlight(expected)));
+}
@@ -0,0 +1,159 @@
+use std::ffi::OsStr;
+
+use crate::core::parser::Parser;
+use crate::diagnostics::{render_error, render_line_errors, should_color, ColorChoice, Style};
+#[cfg(feature = "serde")]
+use crate::diagnostics::{FileDiagnostic, DIAGNOSTICS_SCHEMA};
+
+use indoc::indoc;
+use test_case::test_case;
+
+#[test_case(ColorChoice::Auto, true, None, true; "Auto on a terminal")]
+#[test_case(ColorChoice::Auto, false, None, false; "Auto when piped")]
+#[test_case(ColorChoice::Auto, true, Some("1"), false; "Auto with NO_COLOR")]
+#[test_case(ColorChoice::Auto, true, Some(""), true; "Auto with empty NO_COLOR")]
+#[test_case(ColorChoice::Always, false, Some("1"), true; "Always")]
+#[test_case(ColorChoice::Never, true, None, false; "Never")]
+fn test_should_color(
+    choice: ColorChoice,
+    is_terminal: bool,
+    no_color: Option<&str>,
+    expected: bool,
+) {
+    assert_eq!(
+        should_color(&choice, is_terminal, no_color.map(OsStr::new)),
+        expected
+    );
+}
+
+/// Parse ``source`` and render its first error.
+fn render_first_error(source: &str, style: Style) -> String {
+    let mut parser = Parser::new(source).unwrap();
+    parser.parse_program();
+    render_error(&parser.errors[0], "test.vv", source, style)
+}
+
+#[test]
+fn test_render_error_plain() {
+    let rendered = render_first_error("let x = 5;\nlet = 10;\n", Style::new(false));
+
+    assert_eq!(
+        rendered,
+        indoc! {"
+            test.vv:2:5: error: Expected identifier, found '='
+             2 | let = 10;
+               |     ^
+        "}
+    );
+}
+
+#[test]
+fn test_render_error_colored() {
+    let rendered = render_first_error("let x = 5;\nlet = 10;\n", Style::new(true));
+
+    assert_eq!(
+        rendered,
+        concat!(
+            "\x1b[2mtest.vv:2:5:\x1b[0m \x1b[1;31merror\x1b[0m: Expected identifier, found '='\n",
+            " 2 | let = 10;\n",
+            "   |     \x1b[31m^\x1b[0m\n",
+        )
+    );
+}
+
+#[test]
+fn test_render_error_after_tab() {
+    let rendered = render_first_error("\tlet = 10;", Style::new(false));
+
+    assert!(rendered.ends_with(" 1 | \tlet = 10;\n   | \t    ^\n"));
+}
+
+#[test]
+fn test_render_line_errors() {
+    let rendered = render_line_errors(
+        "let = 10;",
+        &[
+            (4, "Expected identifier, found '='"),
+            (6, "Unsupported token: '10'"),
+        ],
+        Style::new(false),
+    );
+
+    assert_eq!(
+        rendered,
+        indoc! {"
+            let = 10;
+                ^ error: Expected identifier, found '='
+                  ^ error: Unsupported token: '10'
+        "}
+    );
+}
+
+#[test]
+fn test_render_line_errors_past_the_end() {
+    let rendered = render_line_errors("let x = 5", &[(11, "Expected ';'")], Style::new(false));
+
+    assert_eq!(rendered, "let x = 5\n           ^ error: Expected ';'\n");
+}
+
+#[test]
+fn test_render_line_errors_after_tab() {
+    let rendered = render_line_errors("\tlet = 1;", &[(5, "Oops")], Style::new(false));
+
+    assert_eq!(rendered, "\tlet = 1;\n\t    ^ error: Oops\n");
+}
+
+#[test]
+fn test_render_line_errors_colored() {
+    let rendered = render_line_errors("x", &[(0, "Oops")], Style::new(true));
+
+    assert_eq!(
+        rendered,
+        "x\n\x1b[31m^\x1b[0m \x1b[1;31merror\x1b[0m: Oops\n"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_file_diagnostic_from_parser_error() {
+    let mut parser = Parser::new("let x = 5;\nlet = 10;\n").unwrap();
+    parser.parse_program();
+
+    let diagnostic = FileDiagnostic::from_parser_error("script.vv", &parser.errors[0]);
+    let json = serde_json::to_value(&diagnostic).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "file": "script.vv",
+            "start_line": 2,
+            "start_column": 5,
+            "end_line": 2,
+            "end_column": 6,
+            "code": "unexpected-token",
+            "message": "Expected identifier, found '='",
+        })
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_diagnostics_schema_lists_every_field() {
+    let diagnostic = FileDiagnostic::io_error("script.vv", "Could not read 'script.vv'");
+    let json = serde_json::to_value(&diagnostic).unwrap();
+    assert!(json["start_line"].is_null());
+
+    let mut fields: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    let mut documented: Vec<&str> = DIAGNOSTICS_SCHEMA
+        .lines()
+        .filter(|line| line.starts_with("  ") && !line.starts_with("   "))
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+    fields.sort();
+    documented.sort();
+    assert_eq!(fields, documented);
+}
@@ -0,0 +1,61 @@
+use crate::core::ast::Program;
+use crate::dot::program_to_dot;
+
+/// Parse ``source`` and render it as DOT.
+fn to_dot(source: &str) -> String {
+    let program: Program = source.parse().unwrap();
+    program_to_dot(&program)
+}
+
+#[test]
+fn test_program_to_dot() {
+    let dot = to_dot("let x = 1 + 2;");
+
+    assert!(dot.starts_with("digraph ast {\n"));
+    assert!(dot.ends_with("}\n"));
+    for label in [
+        "Program",
+        "Let",
+        "Identifier: x",
+        "Expression",
+        "Int: 1",
+        "Plus: +",
+        "Int: 2",
+    ] {
+        assert!(
+            dot.contains(&format!("[label=\"{label}\"];")),
+            "Missing '{label}' in {dot}"
+        );
+    }
+    // A tree with 7 nodes has 6 edges
+    assert_eq!(dot.matches(" -> ").count(), 6);
+    assert!(dot.contains("    n0 -> n1;\n"));
+}
+
+#[test]
+fn test_program_to_dot_statements() {
+    let dot = to_dot("let x = 1;\nx += 2;\nreturn;\n");
+
+    // The compound assignment is shown desugared
+    for label in ["Assign", "Identifier: x", "LParen: (", "Return", "Null"] {
+        assert!(
+            dot.contains(&format!("[label=\"{label}\"];")),
+            "Missing '{label}' in {dot}"
+        );
+    }
+}
+
+#[test]
+fn test_program_to_dot_empty() {
+    assert_eq!(
+        program_to_dot(&Program::new()),
+        "digraph ast {\n    node [shape=box];\n    n0 [label=\"Program\"];\n}\n"
+    );
+}
+
+#[test]
+fn test_program_to_dot_escapes_labels() {
+    let dot = to_dot("let x = \"a\\b\";");
+    assert!(dot.contains(r#"[label="Illegal: \""];"#), "{dot}");
+    assert!(dot.contains(r#"[label="Illegal: \\"];"#), "{dot}");
+}
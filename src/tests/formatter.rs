@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use crate::formatter::format_source;
+
+use indoc::indoc;
+use test_case::test_case;
+
+#[test_case("let x=5;\n", "let x = 5;\n"; "Spaces around assignment")]
+#[test_case("let   x   =   5 ;", "let x = 5;\n"; "Extra spaces and no trailing newline")]
+#[test_case("let x = 1+2*3;\n", "let x = 1 + 2 * 3;\n"; "Binary operators")]
+#[test_case("let x = ( 1 + 2 ) * 3;\n", "let x = (1 + 2) * 3;\n"; "Parentheses")]
+#[test_case("let x = add( 1 , 2 );\n", "let x = add(1, 2);\n"; "Call")]
+#[test_case("let x = - 5 * -y;\n", "let x = -5 * -y;\n"; "Unary minus")]
+#[test_case("let x = !true;\n", "let x = !true;\n"; "Bang")]
+#[test_case("let x = items [ 0 ];\n", "let x = items[0];\n"; "Indexing")]
+#[test_case("x+=1;\n", "x += 1;\n"; "Compound assignment")]
+#[test_case("x *= 1+2;\n", "x *= 1 + 2;\n"; "Compound assignment keeps the value as written")]
+#[test_case("x=1;\n", "x = 1;\n"; "Plain assignment")]
+#[test_case("return;\n", "return;\n"; "Bare return")]
+#[test_case("let x = 1 +\n  2;\n", "let x = 1 + 2;\n"; "Expression spanning lines")]
+#[test_case("", ""; "Empty")]
+#[test_case("#!/usr/bin/env vvz-lang\nlet x=1;\n", "#!/usr/bin/env vvz-lang\nlet x = 1;\n"; "Shebang")]
+#[test_case("#!/usr/bin/env vvz-lang\n\n\nlet x=1;", "#!/usr/bin/env vvz-lang\n\nlet x = 1;\n"; "Blank line after the shebang")]
+#[test_case("\u{FEFF}#!/usr/bin/env vvz-lang\r\nlet x=1;\r\n", "#!/usr/bin/env vvz-lang\nlet x = 1;\n"; "Shebang after a byte order mark")]
+#[test_case("let x = 1;\n\nlet y = 2;\n", "let x = 1;\n\nlet y = 2;\n"; "Blank line")]
+#[test_case("\n\nlet x = 1;\n\n\n\nlet y = 2;\n\n", "let x = 1;\n\nlet y = 2;\n"; "Runs of blank lines")]
+#[test_case("let x = 1;\n  \t\nlet y = 2;\n", "let x = 1;\n\nlet y = 2;\n"; "Line with only whitespace")]
+#[test_case("let x = 1 +\n\n  2;\nlet y = 2;\n", "let x = 1 + 2;\nlet y = 2;\n"; "Blank line inside a statement")]
+fn test_format_source(input: &str, expected: &str) {
+    assert_eq!(format_source(input).unwrap(), expected);
+}
+
+#[test]
+fn test_format_source_messy_script() {
+    let input = indoc! {"
+        let x   =5 ;
+
+
+            let y = x*2 ;
+        return   y;"};
+    let expected = indoc! {"
+        let x = 5;
+
+        let y = x * 2;
+        return y;
+    "};
+
+    assert_eq!(format_source(input).unwrap(), expected);
+}
+
+#[test_case("let x = 1; // keep me\n// header\nx += 2;\n", "let x = 1; // keep me\n// header\nx += 2;\n"; "Trailing and own line")]
+#[test_case("let x=1;   // note   \n", "let x = 1; // note\n"; "Trailing spaces")]
+#[test_case("let a = 1; let b = 2; // on b\n", "let a = 1;\nlet b = 2; // on b\n"; "After the last statement of a line")]
+#[test_case("let x = 1 + // one\n  2;\n", "// one\nlet x = 1 + 2;\n"; "Inside a statement")]
+#[test_case("let x = 1;\n// the end", "let x = 1;\n// the end\n"; "After the last statement")]
+#[test_case("// nothing else\n", "// nothing else\n"; "Only a comment")]
+#[test_case("// header\n\nlet x = 1;\n\n// the end\n", "// header\n\nlet x = 1;\n\n// the end\n"; "Blank lines around comments")]
+#[test_case("let x = 1 + // one\n\n  2;\n", "// one\nlet x = 1 + 2;\n"; "Blank line after a comment inside a statement")]
+fn test_format_source_keeps_comments(input: &str, expected: &str) {
+    let formatted = format_source(input).unwrap();
+    assert_eq!(formatted, expected);
+    assert_eq!(format_source(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn test_format_source_error() {
+    let errors = format_source("let = 5;\n").unwrap_err();
+    assert!(!errors.is_empty());
+}
+
+/// Formatting formatted code must not change it
+#[test]
+fn test_format_source_idempotent_on_fixtures() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut dirs = vec![fixtures_dir];
+    let mut num_formatted = 0;
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path.extension().is_none_or(|ext| ext != "vv") {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&path).unwrap();
+            // Scripts with errors can't be formatted
+            let Ok(formatted) = format_source(&source) else {
+                continue;
+            };
+            assert_eq!(
+                format_source(&formatted).unwrap(),
+                formatted,
+                "Formatting {} is not idempotent",
+                path.display()
+            );
+            num_formatted += 1;
+        }
+    }
+
+    assert!(num_formatted > 0);
+}
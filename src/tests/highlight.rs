@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use crate::core::tokens::{Token, TokenType};
+use crate::highlight::{classify, highlight, HighlightFormat, TokenClass};
+
+use test_case::test_case;
+
+/// Remove the ANSI escape codes from ``text``
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to the end of the escape sequence
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+fn read_fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/highlight")
+        .join(name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test_case(TokenType::Let, "let", Some(TokenClass::Keyword); "Keyword")]
+#[test_case(TokenType::And, "and", Some(TokenClass::Keyword); "Keyword operator")]
+#[test_case(TokenType::And, "&&", Some(TokenClass::Operator); "Operator")]
+#[test_case(TokenType::Ident, "x", Some(TokenClass::Identifier); "Identifier")]
+#[test_case(TokenType::Int, "5", Some(TokenClass::Number); "Number")]
+#[test_case(TokenType::Semicolon, ";", Some(TokenClass::Punctuation); "Punctuation")]
+#[test_case(TokenType::Comment, "// note", Some(TokenClass::Comment); "Comment")]
+#[test_case(TokenType::Illegal, "$", Some(TokenClass::Illegal); "Illegal")]
+#[test_case(TokenType::NewLine, "\n", None; "Newline")]
+fn test_classify(r#type: TokenType, literal: &str, expected: Option<TokenClass>) {
+    assert_eq!(classify(&Token::new(r#type, literal)), expected);
+}
+
+#[test]
+fn test_highlight_html_golden() {
+    let source = read_fixture("sample.vv");
+    let expected = read_fixture("sample.html");
+
+    assert_eq!(highlight(&source, HighlightFormat::Html), expected);
+}
+
+#[test_case("let x = 5;\n"; "Simple")]
+#[test_case("let   x =\t5 ; \n\n"; "Odd whitespace")]
+#[test_case("let x = 1 + \\\n    2;\r\n"; "Line continuation and CRLF")]
+#[test_case("#!/usr/bin/env vvlang\nlet x = $;"; "Shebang and illegal char")]
+#[test_case("let 🦀 = 1;"; "Multi-byte char")]
+#[test_case("// header\nlet x = 1; // note\n"; "Comments")]
+#[test_case(""; "Empty")]
+fn test_highlight_ansi_keeps_source(source: &str) {
+    assert_eq!(
+        strip_ansi(&highlight(source, HighlightFormat::Ansi)),
+        source
+    );
+}
+
+#[test]
+fn test_highlight_ansi_keeps_fixture() {
+    let source = read_fixture("sample.vv");
+    assert_eq!(
+        strip_ansi(&highlight(&source, HighlightFormat::Ansi)),
+        source
+    );
+}
+
+#[test]
+fn test_highlight_html_escapes() {
+    assert_eq!(
+        highlight("x<y", HighlightFormat::Html),
+        concat!(
+            "<pre class=\"vvlang\">",
+            "<span class=\"tok-identifier\">x</span>",
+            "<span class=\"tok-operator\">&lt;</span>",
+            "<span class=\"tok-identifier\">y</span>",
+            "</pre>\n"
+        )
+    );
+}
+
+#[test]
+fn test_highlight_comments() {
+    assert_eq!(
+        highlight("x // <note>", HighlightFormat::Html),
+        concat!(
+            "<pre class=\"vvlang\">",
+            "<span class=\"tok-identifier\">x</span> ",
+            "<span class=\"tok-comment\">// &lt;note&gt;</span>",
+            "</pre>\n"
+        )
+    );
+    assert_eq!(
+        highlight("// note", HighlightFormat::Ansi),
+        "\x1b[3;90m// note\x1b[0m"
+    );
+}
@@ -1,4 +1,4 @@
-use crate::core::lexer::Lexer;
+use crate::core::lexer::{DecimalNumberLexer, Lexer, LexerConfig, LexerError, NumberLexer};
 use crate::core::tokens::{Token, TokenType};
 
 use test_case::test_case;
@@ -11,9 +11,61 @@ fn test_new() {
 }
 
 #[test]
-fn test_new_error() {
-    let input = "";
-    assert!(Lexer::new(input).is_err());
+fn test_new_empty() {
+    let mut lexer = Lexer::new("").unwrap();
+
+    let token = lexer.next_token();
+    assert_eq!(token, Token::new(TokenType::EOF, ""));
+    assert_eq!((token.start, token.end, token.line), (0, 0, 1));
+}
+
+#[test]
+fn test_from_reader() {
+    let reader = std::io::Cursor::new("let a = 5;".as_bytes());
+    let mut lexer = Lexer::from_reader(reader).unwrap();
+
+    let expected_tokens = [
+        Token::new(TokenType::Let, "let"),
+        Token::new(TokenType::Ident, "a"),
+        Token::new(TokenType::Assign, "="),
+        Token::new(TokenType::Int, "5"),
+        Token::new(TokenType::Semicolon, ";"),
+        Token::new(TokenType::EOF, ""),
+    ];
+    for expected_token in expected_tokens.iter() {
+        assert_eq!(&lexer.next_token(), expected_token);
+    }
+}
+
+#[test]
+fn test_from_reader_error() {
+    // Invalid UTF-8 can't be read into the lexer
+    let reader = std::io::Cursor::new(&[0xff, 0xfe][..]);
+    assert!(matches!(Lexer::from_reader(reader), Err(LexerError::Io(_))));
+}
+
+// Identifiers can use the whole alphabet, 'z' and 'Z' included
+#[test_case("z"; "Lowercase z")]
+#[test_case("Z"; "Uppercase Z")]
+#[test_case("fizz_BUZZ"; "Mixed case")]
+fn test_identifier_letters(input: &str) {
+    let mut lexer = Lexer::new(input).unwrap();
+    assert_eq!(lexer.next_token(), Token::new(TokenType::Ident, input));
+    assert_eq!(lexer.next_token(), Token::new(TokenType::EOF, ""));
+}
+
+// Token positions
+#[test_case("let five = 5;", vec![(0, 3), (4, 8), (9, 10), (11, 12), (12, 13), (13, 13)]; "Let statement")]
+#[test_case("a == b\r\n!c", vec![(0, 1), (2, 4), (5, 6), (6, 8), (8, 9), (9, 10), (10, 10)]; "Two chars tokens")]
+#[test_case("x\t+  🦀", vec![(0, 1), (2, 3), (5, 6), (6, 6)]; "Whitespace and multi-byte chars")]
+#[test_case("\u{FEFF}x", vec![(1, 2), (2, 2)]; "Positions after a byte order mark")]
+fn test_next_token_positions(input: &str, expected_positions: Vec<(usize, usize)>) {
+    let mut lexer = Lexer::new(input).unwrap();
+    for (i, expected_position) in expected_positions.into_iter().enumerate() {
+        let token = lexer.next_token();
+        eprintln!("{i} - token: {token:?}");
+        assert_eq!((token.start, token.end), expected_position);
+    }
 }
 
 // Simple Parsing
@@ -34,6 +86,114 @@ fn test_new_error() {
         Token::new(TokenType::Semicolon, ";"),
         Token::new(TokenType::EOF, ""),
 ]; "Test for operators and parenthesis")]
+#[test_case("let 🦀 = 5;", vec![
+        Token::new(TokenType::Let, "let"),
+        Token::new(TokenType::Illegal, "🦀"),
+        Token::new(TokenType::Assign, "="),
+        Token::new(TokenType::Int, "5"),
+        Token::new(TokenType::Semicolon, ";"),
+        Token::new(TokenType::EOF, ""),
+]; "Multi-byte chars are read as a single char")]
+#[test_case("let x = 1 + \\\n 2;", vec![
+        Token::new(TokenType::Let, "let"),
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::Assign, "="),
+        Token::new(TokenType::Int, "1"),
+        Token::new(TokenType::Plus, "+"),
+        Token::new(TokenType::Int, "2"),
+        Token::new(TokenType::Semicolon, ";"),
+        Token::new(TokenType::EOF, ""),
+]; "Line continuation with a trailing backslash")]
+#[test_case("1 \\\r\n+ 2", vec![
+        Token::new(TokenType::Int, "1"),
+        Token::new(TokenType::Plus, "+"),
+        Token::new(TokenType::Int, "2"),
+        Token::new(TokenType::EOF, ""),
+]; "Line continuation with Windows-style newlines")]
+#[test_case("1 \\ 2", vec![
+        Token::new(TokenType::Int, "1"),
+        Token::new(TokenType::Illegal, "\\"),
+        Token::new(TokenType::Int, "2"),
+]; "Backslash not followed by a newline")]
+#[test_case("a && b || !c", vec![
+        Token::new(TokenType::Ident, "a"),
+        Token::new(TokenType::And, "&&"),
+        Token::new(TokenType::Ident, "b"),
+        Token::new(TokenType::Or, "||"),
+        Token::new(TokenType::Bang, "!"),
+        Token::new(TokenType::Ident, "c"),
+        Token::new(TokenType::EOF, ""),
+]; "Symbolic logical operators")]
+#[test_case("true and not false or x", vec![
+        Token::new(TokenType::True, "true"),
+        Token::new(TokenType::And, "and"),
+        Token::new(TokenType::Bang, "not"),
+        Token::new(TokenType::False, "false"),
+        Token::new(TokenType::Or, "or"),
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::EOF, ""),
+]; "Word aliases of the logical operators")]
+#[test_case("a & b | c", vec![
+        Token::new(TokenType::Ident, "a"),
+        Token::new(TokenType::Illegal, "&"),
+        Token::new(TokenType::Ident, "b"),
+        Token::new(TokenType::Illegal, "|"),
+        Token::new(TokenType::Ident, "c"),
+]; "Single ampersand and pipe are not supported")]
+#[test_case("a \u{0007} b", vec![
+        Token::new(TokenType::Ident, "a"),
+        Token::new(TokenType::Illegal, "\\u{0007}"),
+        Token::new(TokenType::Ident, "b"),
+]; "Control chars are escaped")]
+#[test_case("a \u{00E9} b", vec![
+        Token::new(TokenType::Ident, "a"),
+        Token::new(TokenType::Illegal, "\u{00E9}"),
+        Token::new(TokenType::Ident, "b"),
+]; "Printable chars are kept as they are")]
+#[test_case("\u{FEFF}let x = 5;", vec![
+        Token::new(TokenType::Let, "let"),
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::Assign, "="),
+        Token::new(TokenType::Int, "5"),
+        Token::new(TokenType::Semicolon, ";"),
+        Token::new(TokenType::EOF, ""),
+]; "Leading byte order mark")]
+#[test_case("#!/usr/bin/env vvlang\nlet x = 5;", vec![
+        Token::new(TokenType::NewLine, "\n"),
+        Token::new(TokenType::Let, "let"),
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::Assign, "="),
+        Token::new(TokenType::Int, "5"),
+        Token::new(TokenType::Semicolon, ";"),
+        Token::new(TokenType::EOF, ""),
+]; "Shebang line")]
+#[test_case("\u{FEFF}#!/usr/bin/env vvlang\r\nx", vec![
+        Token::new(TokenType::NewLine, "\r\n"),
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::EOF, ""),
+]; "Byte order mark followed by a shebang line")]
+#[test_case("x #!y", vec![
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::Illegal, "#"),
+        Token::new(TokenType::Bang, "!"),
+        Token::new(TokenType::Ident, "y"),
+]; "Shebang only counts at the start of the input")]
+#[test_case("xs[0]", vec![
+        Token::new(TokenType::Ident, "xs"),
+        Token::new(TokenType::LBracket, "["),
+        Token::new(TokenType::Int, "0"),
+        Token::new(TokenType::RBracket, "]"),
+        Token::new(TokenType::EOF, ""),
+]; "Brackets")]
+#[test_case("} elif (x) {", vec![
+        Token::new(TokenType::RBrace, "}"),
+        Token::new(TokenType::Elif, "elif"),
+        Token::new(TokenType::LParen, "("),
+        Token::new(TokenType::Ident, "x"),
+        Token::new(TokenType::RParen, ")"),
+        Token::new(TokenType::LBrace, "{"),
+        Token::new(TokenType::EOF, ""),
+]; "Elif keyword")]
 fn test_next_token(input: &str, expected_results: Vec<Token>) {
     let mut lexer = Lexer::new(input).unwrap();
     for (i, expected_result) in expected_results.iter().enumerate() {
@@ -223,3 +383,495 @@ fn test_next_token_more_complex_string(input: &str, expected_results: Vec<Token>
         assert_eq!(&token, expected_token);
     }
 }
+
+// Indentation-based blocks
+#[test_case(
+"let a = 1;
+if a
+    let b = 2;
+let c = 3;",
+vec![
+    Token::new(TokenType::Let, "let"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::Assign, "="),
+    Token::new(TokenType::Int, "1"),
+    Token::new(TokenType::Semicolon, ";"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Let, "let"),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::Assign, "="),
+    Token::new(TokenType::Int, "2"),
+    Token::new(TokenType::Semicolon, ";"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::Let, "let"),
+    Token::new(TokenType::Ident, "c"),
+    Token::new(TokenType::Assign, "="),
+    Token::new(TokenType::Int, "3"),
+    Token::new(TokenType::Semicolon, ";"),
+    Token::new(TokenType::EOF, ""),
+]; "Simple indented block")]
+#[test_case(
+"if a
+  if b
+
+    c
+d",
+vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "  "),
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Ident, "c"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::Ident, "d"),
+    Token::new(TokenType::EOF, ""),
+]; "Nested blocks closed at once, blank lines ignored")]
+#[test_case(
+"if a
+    b",
+vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::EOF, ""),
+]; "Blocks still open at the end of the input are closed")]
+#[test_case(
+"if a
+    b
+  c",
+vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::Illegal, "  "),
+]; "Dedent to a width that matches no enclosing block")]
+#[test_case(
+"if a
+\t b",
+vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Illegal, "\t "),
+]; "Tabs and spaces mixed in a single line")]
+#[test_case(
+"if a
+\tb
+if c
+    d",
+vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "\t"),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "c"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Illegal, "    "),
+]; "Tabs and spaces mixed across lines")]
+#[test_case("if a\n    b", vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::EOF, ""),
+]; "Block open at the end of the input")]
+#[test_case("if a\n    b   ", vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::EOF, ""),
+]; "Trailing whitespace before the end of the input")]
+#[test_case("if a\n    b // c", vec![
+    Token::new(TokenType::If, "if"),
+    Token::new(TokenType::Ident, "a"),
+    Token::new(TokenType::NewLine, "\n"),
+    Token::new(TokenType::Indent, "    "),
+    Token::new(TokenType::Ident, "b"),
+    Token::new(TokenType::Dedent, ""),
+    Token::new(TokenType::EOF, ""),
+]; "Trailing comment before the end of the input")]
+fn test_next_token_indentation_blocks(input: &str, expected_results: Vec<Token>) {
+    let config = LexerConfig {
+        indentation_blocks: true,
+        ..LexerConfig::default()
+    };
+    let mut lexer = Lexer::with_config(input, config).unwrap();
+    for (i, expected_token) in expected_results.iter().enumerate() {
+        let token = lexer.next_token();
+        eprintln!("{i} - token: {token:?}");
+        assert_eq!(&token, expected_token);
+    }
+}
+
+// Custom number formats
+/// Accepts octal literals like '0o17', on top of the usual decimal ones
+struct OctalNumberLexer;
+
+impl NumberLexer for OctalNumberLexer {
+    fn scan(&self, source: &str) -> Option<usize> {
+        match source.strip_prefix("0o") {
+            Some(rest) => {
+                let num_digits = rest.chars().take_while(|c| c.is_digit(8)).count();
+                Some(2 + num_digits)
+            }
+            None => DecimalNumberLexer.scan(source),
+        }
+    }
+}
+
+#[test_case("let x = 0o17;", vec![
+    Token::new(TokenType::Let, "let"),
+    Token::new(TokenType::Ident, "x"),
+    Token::new(TokenType::Assign, "="),
+    Token::new(TokenType::Int, "0o17"),
+    Token::new(TokenType::Semicolon, ";"),
+    Token::new(TokenType::EOF, ""),
+]; "Octal literal")]
+#[test_case("10 + 0o7", vec![
+    Token::new(TokenType::Int, "10"),
+    Token::new(TokenType::Plus, "+"),
+    Token::new(TokenType::Int, "0o7"),
+    Token::new(TokenType::EOF, ""),
+]; "Decimal literals are still supported")]
+fn test_next_token_custom_number_lexer(input: &str, expected_results: Vec<Token>) {
+    let mut lexer = Lexer::new(input)
+        .unwrap()
+        .with_number_lexer(OctalNumberLexer);
+    for (i, expected_token) in expected_results.iter().enumerate() {
+        let token = lexer.next_token();
+        eprintln!("{i} - token: {token:?}");
+        assert_eq!(&token, expected_token);
+    }
+}
+
+#[test]
+fn test_peek_token() {
+    let mut lexer = Lexer::new("let x = 5;").unwrap();
+
+    let peeked = lexer.peek_token();
+    assert_eq!(peeked, Token::new(TokenType::Let, "let"));
+    // Peeking twice doesn't advance
+    assert_eq!(lexer.peek_token(), peeked);
+
+    let next = lexer.next_token();
+    assert_eq!(next, peeked);
+    assert_eq!((next.start, next.end), (peeked.start, peeked.end));
+    assert_eq!(lexer.next_token(), Token::new(TokenType::Ident, "x"));
+}
+
+#[test_case(LexerConfig::default(); "With newlines")]
+#[test_case(LexerConfig { emit_newlines: false, ..LexerConfig::default() }; "Without newlines")]
+fn test_peek_token_keeps_line(config: LexerConfig) {
+    let mut lexer = Lexer::with_config("let x\n\ny", config).unwrap();
+    lexer.next_token();
+    lexer.next_token();
+    assert_eq!(lexer.line(), 1);
+
+    // Peeking reads past the newlines, but we're still on the first line
+    let peeked = lexer.peek_token();
+    assert_eq!(lexer.line(), 1);
+    lexer.peek_token();
+    assert_eq!(lexer.line(), 1);
+
+    assert_eq!(lexer.next_token(), peeked);
+    assert!(lexer.line() > 1);
+}
+
+#[test]
+fn test_peek_token_with_pending_indentation() {
+    let config = LexerConfig {
+        indentation_blocks: true,
+        ..LexerConfig::default()
+    };
+    let mut lexer = Lexer::with_config("if x\n    y\n", config).unwrap();
+    for _ in 0..3 {
+        lexer.next_token();
+    }
+
+    assert_eq!(lexer.peek_token(), Token::new(TokenType::Indent, "    "));
+    assert_eq!(lexer.next_token(), Token::new(TokenType::Indent, "    "));
+    assert_eq!(lexer.peek_token(), Token::new(TokenType::Ident, "y"));
+    assert_eq!(lexer.next_token(), Token::new(TokenType::Ident, "y"));
+}
+
+#[test]
+fn test_next_token_without_newlines() {
+    let config = LexerConfig {
+        emit_newlines: false,
+        ..LexerConfig::default()
+    };
+    let mut lexer = Lexer::with_config("let x = 5;\r\nlet y = 6;\n\nlet z = $;\n", config).unwrap();
+
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        if token.r#type == TokenType::Illegal {
+            // The error is still reported on the right line
+            assert_eq!(lexer.line(), 4);
+        }
+        if token.r#type == TokenType::EOF {
+            break;
+        }
+        tokens.push(token);
+    }
+
+    assert!(tokens.iter().all(|t| t.r#type != TokenType::NewLine));
+    assert!(tokens.iter().any(|t| t.r#type == TokenType::Illegal));
+    assert_eq!(tokens.len(), 15);
+    assert_eq!(lexer.line(), 5);
+}
+
+#[test]
+fn test_next_token_without_newlines_keeps_indentation_blocks() {
+    let config = LexerConfig {
+        indentation_blocks: true,
+        emit_newlines: false,
+        ..LexerConfig::default()
+    };
+    let mut lexer = Lexer::with_config("if x\n    y\nz", config).unwrap();
+
+    let token_types: Vec<TokenType> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token.r#type)
+    })
+    .collect();
+    assert_eq!(
+        token_types,
+        vec![
+            TokenType::If,
+            TokenType::Ident,
+            TokenType::Indent,
+            TokenType::Ident,
+            TokenType::Dedent,
+            TokenType::Ident,
+        ]
+    );
+}
+
+#[test]
+fn test_next_token_many_blank_lines_without_newlines() {
+    // Each skipped blank line used to cost a stack frame
+    let config = LexerConfig {
+        indentation_blocks: true,
+        emit_newlines: false,
+        ..LexerConfig::default()
+    };
+    let input = format!("x{}y", "\n".repeat(10_000));
+    let mut lexer = Lexer::with_config(&input, config).unwrap();
+
+    assert_eq!(lexer.next_token(), Token::new(TokenType::Ident, "x"));
+    let token = lexer.next_token();
+    assert_eq!(token, Token::new(TokenType::Ident, "y"));
+    assert_eq!(token.line, 10_001);
+    assert_eq!(lexer.next_token().r#type, TokenType::EOF);
+}
+
+#[test_case("255", Some(255); "Small integer")]
+#[test_case("9223372036854775807", Some(i64::MAX); "Largest integer")]
+#[test_case("9223372036854775808", None; "Overflowing integer")]
+fn test_next_token_int_value(input: &str, expected_value: Option<i64>) {
+    let mut lexer = Lexer::new(input).unwrap();
+    let token = lexer.next_token();
+
+    assert_eq!(token, Token::new(TokenType::Int, input));
+    assert_eq!(token.value, expected_value);
+}
+
+#[test]
+fn test_next_token_no_value_for_other_tokens() {
+    let mut lexer = Lexer::new("x").unwrap();
+    assert_eq!(lexer.next_token().value, None);
+}
+
+#[test]
+fn test_next_token_compound_assignments() {
+    let mut lexer = Lexer::new("x += 1; x -= 2; x *= 3; x /= 4; x + = 5;").unwrap();
+    let operators: Vec<Token> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token)
+    })
+    .filter(|token| {
+        !matches!(
+            token.r#type,
+            TokenType::Ident | TokenType::Int | TokenType::Semicolon
+        )
+    })
+    .collect();
+
+    assert_eq!(
+        operators,
+        vec![
+            Token::new(TokenType::PlusAssign, "+="),
+            Token::new(TokenType::MinusAssign, "-="),
+            Token::new(TokenType::AsteriskAssign, "*="),
+            Token::new(TokenType::SlashAssign, "/="),
+            // Only when written together
+            Token::new(TokenType::Plus, "+"),
+            Token::new(TokenType::Assign, "="),
+        ]
+    );
+}
+
+#[test]
+fn test_next_token_line_and_char_offset() {
+    let mut lexer = Lexer::new("let x = 1 + \\\n    2;\nlet 🦀 = 3;").unwrap();
+    let locations: Vec<(String, usize, usize)> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some((token.literal, token.line, token.char_offset))
+    })
+    .collect();
+
+    let expected: Vec<(String, usize, usize)> = [
+        ("let", 1, 0),
+        ("x", 1, 4),
+        ("=", 1, 6),
+        ("1", 1, 8),
+        ("+", 1, 10),
+        // The line continuation doesn't produce a NewLine token
+        ("2", 2, 4),
+        (";", 2, 5),
+        ("\n", 2, 6),
+        ("let", 3, 0),
+        ("🦀", 3, 4),
+        ("=", 3, 6),
+        ("3", 3, 8),
+        (";", 3, 9),
+    ]
+    .into_iter()
+    .map(|(literal, line, offset)| (literal.to_owned(), line, offset))
+    .collect();
+    assert_eq!(locations, expected);
+}
+
+/// The types of all the tokens of ``input``, up to EOF excluded
+fn token_types(mut lexer: Lexer) -> Vec<TokenType> {
+    std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token.r#type)
+    })
+    .collect()
+}
+
+#[test_case("//", "x = 1 // note\n"; "Default prefix")]
+#[test_case("#", "x = 1 # note\n"; "Shell-style prefix")]
+#[test_case("--", "x = 1 -- note\n"; "Multi-char prefix")]
+#[test_case("#", "#!/usr/bin/env vvlang\nx = 1 # note\n"; "Shebang")]
+#[test_case("#", "# comment\nx = 1 # note\n"; "Comment on its own line")]
+fn test_next_token_comments(prefix: &str, input: &str) {
+    let config = LexerConfig {
+        comment_prefix: prefix.to_owned(),
+        ..LexerConfig::default()
+    };
+    let mut expected = vec![
+        TokenType::Ident,
+        TokenType::Assign,
+        TokenType::Int,
+        TokenType::NewLine,
+    ];
+    if input.starts_with('#') {
+        expected.insert(0, TokenType::NewLine);
+    }
+
+    assert_eq!(
+        token_types(Lexer::with_config(input, config).unwrap()),
+        expected
+    );
+}
+
+#[test]
+fn test_next_token_comment_prefix_only_when_configured() {
+    let config = LexerConfig {
+        comment_prefix: "#".to_owned(),
+        ..LexerConfig::default()
+    };
+    // With '#' as the prefix, '//' is just two slashes
+    assert_eq!(
+        token_types(Lexer::with_config("1 // 2", config).unwrap()),
+        vec![
+            TokenType::Int,
+            TokenType::Slash,
+            TokenType::Slash,
+            TokenType::Int
+        ]
+    );
+    // And with the default prefix, '#' is illegal
+    assert_eq!(
+        token_types(Lexer::new("1 # 2").unwrap()),
+        vec![TokenType::Int, TokenType::Illegal, TokenType::Int]
+    );
+}
+
+#[test]
+fn test_next_token_empty_comment_prefix() {
+    let config = LexerConfig {
+        comment_prefix: String::new(),
+        ..LexerConfig::default()
+    };
+    assert_eq!(
+        token_types(Lexer::with_config("1 // 2", config).unwrap()),
+        vec![
+            TokenType::Int,
+            TokenType::Slash,
+            TokenType::Slash,
+            TokenType::Int
+        ]
+    );
+}
+
+#[test]
+fn test_next_token_emit_comments() {
+    let config = LexerConfig {
+        emit_comments: true,
+        ..LexerConfig::default()
+    };
+    let mut lexer = Lexer::with_config("// header\nx = 1 // note  \n", config).unwrap();
+
+    let comment = lexer.next_token();
+    assert_eq!(comment, Token::new(TokenType::Comment, "// header"));
+    assert_eq!((comment.start, comment.end, comment.line), (0, 9, 1));
+
+    let tokens: Vec<Token> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token)
+    })
+    .collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::new(TokenType::NewLine, "\n"),
+            Token::new(TokenType::Ident, "x"),
+            Token::new(TokenType::Assign, "="),
+            Token::new(TokenType::Int, "1"),
+            Token::new(TokenType::Comment, "// note  "),
+            Token::new(TokenType::NewLine, "\n"),
+        ]
+    );
+}
@@ -0,0 +1,14 @@
+use log::LevelFilter;
+
+use crate::logging::level_for;
+
+use test_case::test_case;
+
+#[test_case(0, false, LevelFilter::Warn; "Default")]
+#[test_case(1, false, LevelFilter::Debug; "Verbose")]
+#[test_case(2, false, LevelFilter::Trace; "Very verbose")]
+#[test_case(3, false, LevelFilter::Trace; "More than very verbose")]
+#[test_case(0, true, LevelFilter::Off; "Quiet")]
+fn test_level_for(verbose: u8, quiet: bool, expected: LevelFilter) {
+    assert_eq!(level_for(verbose, quiet), expected);
+}
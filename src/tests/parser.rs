@@ -1,5 +1,6 @@
-use crate::core::parser::ast::Statement;
-use crate::core::parser::Parser;
+use crate::core::ast::Statement;
+use crate::core::parser::{desugar_compound_assign, Parser, ParserError, ParserErrorKind};
+use crate::core::span::{Span, Spanned};
 use crate::core::tokens::{Token, TokenType};
 
 use test_case::test_case;
@@ -8,6 +9,7 @@ use super::ast;
 
 #[test_case("let x = 5;", vec!["x"], 1; "Simple parser test with a single let assignment")]
 #[test_case("let x = 5; let y = 10;", vec!["x", "y"], 2; "Simple parser test with two let assignments on a single line")]
+#[test_case("let x\n  = 5;", vec!["x"], 1; "Let assignment split over two lines")]
 #[test_case("
 let x = 5;
 let y = 10;
@@ -23,13 +25,13 @@ fn test_let_statements(
     let program = parser.parse_program();
 
     assert_eq!(
-        program.statements.len(),
+        program.len(),
         expected_num_statements,
         "Program should contain {expected_num_statements} statements"
     );
 
     for (statement, expected_identifier_name) in
-        std::iter::zip(program.statements.iter(), expected_identifiers_names)
+        std::iter::zip(&program, expected_identifiers_names)
     {
         eprintln!(
             "Current statement: '{statement}' - expected identifier name: {}",
@@ -39,15 +41,26 @@ fn test_let_statements(
         // Every statement should be a let assignment
         assert!(std::matches!(statement, Statement::Assignment(_)));
 
-        match statement {
-            Statement::Assignment(let_statement) => {
-                assert_eq!(let_statement.identifier.name, expected_identifier_name);
-            }
-            _ => {}
+        if let Statement::Assignment(let_statement) = statement {
+            assert_eq!(let_statement.identifier.name, expected_identifier_name);
         }
     }
 }
 
+#[test_case("let x = 5;", "5"; "Let statement with a single literal")]
+#[test_case("let x = 5 + 10;", "5 + 10"; "Let statement with an infix expression")]
+fn test_let_statement_value_expr(input: &str, expected_literal: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    let program = parser.parse_program();
+
+    match program.first() {
+        Some(Statement::Assignment(let_statement)) => {
+            assert_eq!(let_statement.value_expr().literal(), expected_literal);
+        }
+        other => panic!("Expected a let statement, found {other:?}"),
+    }
+}
+
 #[test_case("return 5;", 1; "Simple parser test with a single return")]
 #[test_case("
 return 5;
@@ -60,20 +73,413 @@ fn test_return_statements(input: &str, expected_num_statements: usize) {
     let program = parser.parse_program();
 
     assert_eq!(
-        program.statements.len(),
+        program.len(),
         expected_num_statements,
         "Program should contain {expected_num_statements} statements"
     );
 
-    for i in 0..expected_num_statements {
-        let current_statement = program.statements.get(i).unwrap();
+    for current_statement in &program {
         assert!(std::matches!(current_statement, ast::Statement::Return(_)));
 
-        match current_statement {
-            ast::Statement::Return(rs) => {
-                assert_eq!(rs.token.r#type, TokenType::Return);
-            }
-            _ => {}
+        if let ast::Statement::Return(rs) = current_statement {
+            assert_eq!(rs.token.r#type, TokenType::Return);
         }
     }
 }
+
+#[test_case("return;"; "Bare return")]
+#[test_case("let x = 1;\nreturn ;\n"; "Bare return with a space")]
+fn test_bare_return(input: &str) {
+    let program: ast::Program = input.parse().unwrap();
+
+    let Some(Statement::Return(return_statement)) = program.last() else {
+        panic!(
+            "Expected a return statement, found {:?}",
+            program.statements()
+        );
+    };
+    assert!(return_statement.value.is_null());
+    assert_eq!(return_statement.value.node, ast::Expression::null());
+    // The empty span sits right before the ';'
+    let semicolon_offset = input.rfind(';').unwrap();
+    assert_eq!(
+        return_statement.value.span,
+        Span::empty_at(semicolon_offset)
+    );
+    assert_eq!(program.last().unwrap().to_string(), "return;");
+}
+
+#[test_case("let x = 5 + 10;", Span::new(8, 14), "5 + 10"; "Let statement")]
+#[test_case("return add(1, 2);", Span::new(7, 16), "add ( 1 , 2 )"; "Return statement")]
+#[test_case("return;", Span::new(6, 6), ""; "Empty expression")]
+fn test_spanned_expression(input: &str, expected_span: Span, expected_literal: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    let program = parser.parse_program();
+
+    let expression = match program.first() {
+        Some(Statement::Assignment(let_statement)) => &let_statement.value,
+        Some(Statement::Return(return_statement)) => &return_statement.value,
+        other => panic!("Expected a let or return statement, found {other:?}"),
+    };
+
+    assert_eq!(expression.span, expected_span);
+    // The inner node can be reached through Deref too
+    assert_eq!(expression.literal(), expected_literal);
+    assert_eq!(expression.node.literal(), expected_literal);
+}
+
+#[test]
+fn test_statements_are_send_and_sync() {
+    // Embedders should be able to share parsed programs across threads
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ast::Statement>();
+}
+
+#[test]
+fn test_program_from_str() {
+    let program: ast::Program = "let x = 5;\nreturn x;".parse().unwrap();
+    assert_eq!(program.len(), 2);
+    assert!(std::matches!(
+        program.first(),
+        Some(Statement::Assignment(_))
+    ));
+    assert!(std::matches!(program.last(), Some(Statement::Return(_))));
+}
+
+#[test_case(""; "Empty input")]
+#[test_case("\n  \n"; "Blank lines")]
+#[test_case("// just a comment\n"; "Comment only")]
+fn test_program_from_str_empty(input: &str) {
+    let program: ast::Program = input.parse().unwrap();
+    assert!(program.is_empty());
+}
+
+#[test_case("let = 5;"; "Let statement without an identifier")]
+fn test_program_from_str_error(input: &str) {
+    let errors = input.parse::<ast::Program>().unwrap_err();
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn test_expect() {
+    let mut parser = Parser::new("let x = 5;").unwrap();
+
+    let token = parser.expect(TokenType::Ident).unwrap();
+    assert_eq!(token, Token::new(TokenType::Ident, "x"));
+    assert_eq!(parser.current_token, Token::new(TokenType::Ident, "x"));
+    assert_eq!(parser.peek_token, Token::new(TokenType::Assign, "="));
+}
+
+#[test]
+fn test_expect_error() {
+    let mut parser = Parser::new("let 5 = x;").unwrap();
+
+    let error = parser.expect(TokenType::Ident).unwrap_err();
+    assert_eq!(
+        *error.kind,
+        ParserErrorKind::UnexpectedToken {
+            expected: vec![TokenType::Ident],
+            found: Token::new(TokenType::Int, "5"),
+        }
+    );
+    assert_eq!(error.message, "Expected identifier, found '5'");
+    assert_eq!(error.line_num, 1);
+
+    // We shouldn't have moved
+    assert_eq!(parser.current_token, Token::new(TokenType::Let, "let"));
+}
+
+#[test_case("let 5 = 3;", TokenType::Ident, Token::new(TokenType::Int, "5"), "Expected identifier, found '5'"; "Missing identifier")]
+#[test_case("let x 5;", TokenType::Assign, Token::new(TokenType::Int, "5"), "Expected '=', found '5'"; "Missing assign")]
+fn test_malformed_let_error(
+    input: &str,
+    expected_type: TokenType,
+    expected_found: Token,
+    expected_message: &str,
+) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    let error = &parser.errors[0];
+    let ParserErrorKind::UnexpectedToken { expected, found } = &*error.kind else {
+        panic!("Expected an unexpected token, found {:?}", error.kind);
+    };
+    assert_eq!(expected, &vec![expected_type]);
+    assert_eq!(found, &expected_found);
+    // The error points at the token we found
+    assert_eq!(
+        (error.line_num, error.char_offset),
+        (found.line, found.char_offset)
+    );
+    assert_eq!(error.message, expected_message);
+}
+
+#[test_case("let x = 5", "a 'let' statement"; "Let")]
+#[test_case("x += 5", "an assignment"; "Assignment")]
+#[test_case("return x", "a 'return' statement"; "Return")]
+fn test_unterminated_statement_error(input: &str, expected_while_parsing: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    let ParserErrorKind::UnexpectedEof { while_parsing } = *parser.errors[0].kind else {
+        panic!(
+            "Expected an unexpected EOF, found {:?}",
+            parser.errors[0].kind
+        );
+    };
+    assert_eq!(while_parsing, expected_while_parsing);
+}
+
+#[test_case("let = 5;", 1, 4; "First line")]
+#[test_case("let x = 5;\n  let = 10;", 2, 6; "Indented second line")]
+#[test_case("let x = 1\n+ 2;\nlet = 3;", 3, 4; "Expression spanning lines")]
+#[test_case("let x = 1 + \\\n    2;\nlet = 3;", 3, 4; "After a line continuation")]
+#[test_case("let x = 1;\r\nlet = 3;", 2, 4; "Windows-style newline")]
+fn test_error_position(input: &str, expected_line: usize, expected_offset: usize) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    let error = &parser.errors[0];
+    assert_eq!(error.line_num, expected_line);
+    assert_eq!(error.char_offset, expected_offset);
+}
+
+#[test]
+fn test_if_statement_is_an_error() {
+    let mut parser = Parser::new("if x {\n").unwrap();
+    parser.parse_program();
+
+    assert_eq!(
+        *parser.errors[0].kind,
+        ParserErrorKind::UnsupportedToken {
+            found: Token::new(TokenType::If, "if"),
+        }
+    );
+    assert_eq!(
+        parser.errors[0].message,
+        "'if' statements are not supported yet"
+    );
+}
+
+#[test_case("let x = (1 + 2;", 1, 8, "Unclosed '(' opened at line 1, col 9"; "Unclosed paren")]
+#[test_case("let x = 5;\nlet f = fn() {\n  return x;\n", 2, 13, "Unclosed '{' opened at line 2, col 14"; "Unclosed brace")]
+#[test_case("let x = [(1);", 1, 8, "Unclosed '[' opened at line 1, col 9"; "Unclosed bracket around closed paren")]
+fn test_unclosed_delimiter(input: &str, line: usize, offset: usize, message: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    let error = parser
+        .errors
+        .iter()
+        .find(|e| matches!(*e.kind, ParserErrorKind::UnclosedDelimiter { .. }))
+        .expect("The unclosed delimiter should be reported");
+    assert_eq!(error.line_num, line);
+    assert_eq!(error.char_offset, offset);
+    assert_eq!(error.message, message);
+}
+
+#[test]
+fn test_closed_delimiters() {
+    let mut parser = Parser::new("let x = ((1) + [2]);\nlet f = fn() { 1 };").unwrap();
+    parser.parse_program();
+
+    assert!(parser
+        .errors
+        .iter()
+        .all(|e| !matches!(*e.kind, ParserErrorKind::UnclosedDelimiter { .. })));
+}
+
+#[test]
+fn test_parse_large_program() {
+    let input: String = (0..250)
+        .map(|i| format!("let x = {i};\n\nreturn x;\n"))
+        .collect();
+
+    let mut parser = Parser::new(&input).unwrap();
+    let program = parser.parse_program();
+
+    assert!(parser.errors.is_empty());
+    assert_eq!(program.len(), 500);
+}
+
+#[test]
+fn test_parse_many_blank_lines() {
+    // The statements hint only counts lines with something on them
+    let input = "\n".repeat(2000) + "let x = 5;\n";
+    let mut parser = Parser::new(&input).unwrap();
+    assert_eq!(parser.statements_hint, 1);
+
+    let program = parser.parse_program();
+    assert!(parser.errors.is_empty());
+    assert_eq!(program.len(), 1);
+
+    // And stays small no matter how big the input is, as we reserve
+    // room for that many statements before parsing
+    let input = "x;\n".repeat(10_000_000);
+    assert_eq!(Parser::new(&input).unwrap().statements_hint, 1024);
+}
+
+#[test_case("elif (x) { return 1; }", "'elif' can only follow the block of an 'if'"; "Elif")]
+#[test_case("let x = 5;\nelse { return 1; }", "'else' can only follow the block of an 'if'"; "Else")]
+fn test_else_outside_if_chain(input: &str, expected_message: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    assert!(matches!(
+        *parser.errors[0].kind,
+        ParserErrorKind::MisplacedKeyword { .. }
+    ));
+    assert_eq!(parser.errors[0].message, expected_message);
+}
+
+#[test_case("let x = (a];", "Mismatched delimiter: expected ')' to close '(' at line 1, found ']'"; "Paren closed by bracket")]
+#[test_case("let x = {a);", "Mismatched delimiter: expected '}' to close '{' at line 1, found ')'"; "Brace closed by paren")]
+#[test_case("let x = [\na};", "Mismatched delimiter: expected ']' to close '[' at line 1, found '}'"; "Bracket closed by brace")]
+fn test_mismatched_delimiter(input: &str, message: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    let errors: Vec<&ParserError> = parser
+        .errors
+        .iter()
+        .filter(|e| {
+            matches!(
+                *e.kind,
+                ParserErrorKind::MismatchedDelimiter { .. }
+                    | ParserErrorKind::UnclosedDelimiter { .. }
+            )
+        })
+        .collect();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        *errors[0].kind,
+        ParserErrorKind::MismatchedDelimiter { .. }
+    ));
+    assert_eq!(errors[0].message, message);
+}
+
+#[test_case("let x = 1 + 2);", 1, 13, "Unmatched ')'"; "Paren")]
+#[test_case("let x = [1];\nlet y = 2 }", 2, 10, "Unmatched '}'"; "Brace after a closed bracket")]
+#[test_case("let x = (1));", 1, 11, "Unmatched ')'"; "One closer too many")]
+fn test_unmatched_delimiter(input: &str, line: usize, offset: usize, message: &str) {
+    let mut parser = Parser::new(input).unwrap();
+    parser.parse_program();
+
+    let errors: Vec<&ParserError> = parser
+        .errors
+        .iter()
+        .filter(|e| matches!(*e.kind, ParserErrorKind::UnmatchedDelimiter { .. }))
+        .collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].line_num, errors[0].char_offset), (line, offset));
+    assert_eq!(errors[0].width, 1);
+    assert_eq!(errors[0].message, message);
+}
+
+#[test]
+fn test_parse_statement() {
+    let mut parser = Parser::new("let x = 5;\n\nreturn x;\n").unwrap();
+
+    let first = parser.parse_statement().unwrap().unwrap();
+    assert!(matches!(&first, Statement::Assignment(s) if s.identifier.name == "x"));
+
+    let second = parser.parse_statement().unwrap().unwrap();
+    assert!(matches!(second, Statement::Return(_)));
+
+    assert!(parser.parse_statement().unwrap().is_none());
+    // Once at the end, it stays there
+    assert!(parser.parse_statement().unwrap().is_none());
+}
+
+#[test]
+fn test_parse_statement_error() {
+    let mut parser = Parser::new("else;\nreturn x;\n").unwrap();
+
+    let error = parser.parse_statement().unwrap_err();
+    assert_eq!(
+        *error.kind,
+        ParserErrorKind::MisplacedKeyword {
+            found: Token::new(TokenType::Else, "else"),
+        }
+    );
+    // The parser moves past the rest of the statement, without recording the error
+    assert!(parser.errors.is_empty());
+
+    let statement = parser.parse_statement().unwrap().unwrap();
+    assert!(matches!(statement, Statement::Return(_)));
+}
+
+#[test_case("x += 1;", "x = x + ( 1 );"; "Plus")]
+#[test_case("x -= 2;", "x = x - ( 2 );"; "Minus")]
+#[test_case("x *= 1 + 2;", "x = x * ( 1 + 2 );"; "Asterisk keeps precedence")]
+#[test_case("x /= y;", "x = x / ( y );"; "Slash")]
+#[test_case("x = 5;", "x = 5;"; "Plain assignment")]
+fn test_assign_statement(input: &str, expected: &str) {
+    let program: ast::Program = input.parse().unwrap();
+
+    assert_eq!(program.len(), 1);
+    assert!(matches!(program.first(), Some(Statement::Reassignment(_))));
+    assert_eq!(program.first().unwrap().to_string(), expected);
+}
+
+#[test_case(TokenType::PlusAssign, TokenType::Plus; "Plus")]
+#[test_case(TokenType::MinusAssign, TokenType::Minus; "Minus")]
+#[test_case(TokenType::AsteriskAssign, TokenType::Asterisk; "Asterisk")]
+#[test_case(TokenType::SlashAssign, TokenType::Slash; "Slash")]
+fn test_desugar_compound_assign(operator_type: TokenType, expected_operator: TokenType) {
+    let identifier = Token::new(TokenType::Ident, "x").with_position(0, 1);
+    let operator =
+        Token::new(operator_type.clone(), &operator_type.to_string()).with_position(2, 4);
+    let value = Spanned::new(
+        ast::Expression {
+            tokens: vec![Token::new(TokenType::Int, "5").with_position(5, 6)],
+        },
+        Span::new(5, 6),
+    );
+
+    let statement = desugar_compound_assign(&identifier, &operator, value);
+
+    assert_eq!(statement.identifier.name, "x");
+    let expected_tokens = vec![
+        Token::new(TokenType::Ident, "x"),
+        Token::new(expected_operator.clone(), &expected_operator.to_string()),
+        Token::new(TokenType::LParen, "("),
+        Token::new(TokenType::Int, "5"),
+        Token::new(TokenType::RParen, ")"),
+    ];
+    assert_eq!(statement.value.tokens, expected_tokens);
+    assert_eq!(statement.value.span, Span::new(0, 6));
+    // The statement still knows how it was written
+    assert_eq!(statement.operator, operator);
+    assert_eq!(
+        statement.written_value(),
+        &[Token::new(TokenType::Int, "5")]
+    );
+}
+
+#[test]
+fn test_statements_on_line() {
+    let input = "let a = 1;\n\nlet b = 2; return b;\nlet c = 1 +\n  2;\nlet \\\n  d = 3;\n";
+    let program: ast::Program = input.parse().unwrap();
+    let statements_on = |line: usize| -> Vec<String> {
+        program
+            .statements_on_line(line)
+            .iter()
+            .map(|statement| match statement {
+                Statement::Assignment(s) => s.identifier.name.clone(),
+                other => other.to_string(),
+            })
+            .collect()
+    };
+
+    assert_eq!(statements_on(1), vec!["a"]);
+    assert!(statements_on(2).is_empty());
+    assert_eq!(statements_on(3), vec!["b", "return b;"]);
+    // Statements spanning several lines cover all of them
+    assert_eq!(statements_on(4), vec!["c"]);
+    assert_eq!(statements_on(5), vec!["c"]);
+    // Starting from the 'let', even when the name is on the next line
+    assert_eq!(statements_on(6), vec!["d"]);
+    assert_eq!(statements_on(7), vec!["d"]);
+    assert!(statements_on(8).is_empty());
+}
@@ -0,0 +1,484 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::repl::{
+    classify_input, complete, history_file, is_exit_command, parse_action, rc_file, Action,
+    Completeness, Entry, Flow, InputBuffer, Session, View,
+};
+
+use vvlang::Program;
+
+use test_case::test_case;
+
+#[test_case("let x = 5;\n"; "Single line")]
+#[test_case("let x = (1 + 2);\n"; "Balanced delimiters")]
+#[test_case("let x = 1);\n"; "Extra closing delimiter")]
+#[test_case("let 5 = x;\n"; "Invalid statement")]
+#[test_case("let x = (1 + 2;\nlet 5 = x;\n"; "Invalid statement after an unclosed delimiter")]
+fn test_complete_line(line: &str) {
+    let mut buffer = InputBuffer::new();
+    assert_eq!(buffer.push_line(line), Entry::Complete(line.to_owned()));
+    assert!(!buffer.is_pending());
+}
+
+#[test_case("let x = 5;\n", Completeness::Complete; "Complete")]
+#[test_case("", Completeness::Complete; "Empty, so nothing to wait for")]
+#[test_case("let x\n", Completeness::Incomplete; "Missing assignment")]
+#[test_case("let x", Completeness::Incomplete; "Missing assignment without a newline")]
+#[test_case("let\n", Completeness::Incomplete; "Missing identifier")]
+#[test_case("let x =\n", Completeness::Incomplete; "Missing expression")]
+#[test_case("let x =", Completeness::Incomplete; "Missing expression without a newline")]
+#[test_case("let x = 5\n", Completeness::Incomplete; "Missing semicolon")]
+#[test_case("return (1 +\n", Completeness::Incomplete; "Unclosed delimiter")]
+#[test_case("let 5 = x;\n", Completeness::Invalid; "Invalid statement")]
+#[test_case("let 5 = (x\n", Completeness::Invalid; "Invalid statement with an unclosed delimiter")]
+#[test_case("let x = 5;\nlet = 3\n", Completeness::Invalid; "Invalid statement before the end")]
+fn test_classify_input(source: &str, expected: Completeness) {
+    assert_eq!(classify_input(source), expected);
+}
+
+#[test]
+fn test_missing_semicolon_waits_for_more_lines() {
+    let mut buffer = InputBuffer::new();
+
+    assert_eq!(buffer.push_line("let x = 1 +\n"), Entry::Incomplete);
+    assert_eq!(
+        buffer.push_line("  2;\n"),
+        Entry::Complete("let x = 1 +\n  2;\n".to_owned())
+    );
+}
+
+#[test]
+fn test_missing_assignment_waits_for_more_lines() {
+    let mut buffer = InputBuffer::new();
+
+    assert_eq!(buffer.push_line("let x\n"), Entry::Incomplete);
+    assert_eq!(
+        buffer.push_line("  = 5;\n"),
+        Entry::Complete("let x\n  = 5;\n".to_owned())
+    );
+}
+
+#[test]
+fn test_multi_line_function() {
+    let mut buffer = InputBuffer::new();
+
+    assert_eq!(
+        buffer.push_line("let add = fn(x, y) {\n"),
+        Entry::Incomplete
+    );
+    assert!(buffer.is_pending());
+    assert_eq!(buffer.push_line("  return x + y;\n"), Entry::Incomplete);
+    assert_eq!(
+        buffer.push_line("};\n"),
+        Entry::Complete("let add = fn(x, y) {\n  return x + y;\n};\n".to_owned())
+    );
+    assert!(!buffer.is_pending());
+}
+
+#[test]
+fn test_nested_braces() {
+    let mut buffer = InputBuffer::new();
+
+    assert_eq!(buffer.push_line("let f = fn() {\n"), Entry::Incomplete);
+    assert_eq!(buffer.push_line("  if (x) { [1,\n"), Entry::Incomplete);
+    assert_eq!(buffer.push_line("  2] }\n"), Entry::Incomplete);
+    assert!(matches!(buffer.push_line("};\n"), Entry::Complete(_)));
+}
+
+#[test_case("\n"; "Empty line")]
+#[test_case("  \r\n"; "Blank line")]
+fn test_abandoned_fragment(line: &str) {
+    let mut buffer = InputBuffer::new();
+
+    assert_eq!(buffer.push_line("let f = fn() {\n"), Entry::Incomplete);
+    assert_eq!(buffer.push_line(line), Entry::Abandoned);
+    assert!(!buffer.is_pending());
+
+    // The next input starts from scratch
+    assert_eq!(
+        buffer.push_line("let x = 5;\n"),
+        Entry::Complete("let x = 5;\n".to_owned())
+    );
+}
+
+#[test]
+fn test_history_file() {
+    assert_eq!(
+        history_file(Some(Path::new("/home/user/.local/share"))),
+        Some(PathBuf::from("/home/user/.local/share/vvlang/history.txt"))
+    );
+    assert_eq!(history_file(None), None);
+}
+
+#[test]
+fn test_rc_file() {
+    let home = Path::new("/home/user");
+    assert_eq!(
+        rc_file(None, Some(home)),
+        Some(PathBuf::from("/home/user/.config/vvlang/init.vv"))
+    );
+    assert_eq!(
+        rc_file(Some(OsString::from("/tmp/custom.vv")), Some(home)),
+        Some(PathBuf::from("/tmp/custom.vv"))
+    );
+    assert_eq!(
+        rc_file(Some(OsString::from("/tmp/custom.vv")), None),
+        Some(PathBuf::from("/tmp/custom.vv"))
+    );
+    // An empty variable is the same as an unset one
+    assert_eq!(
+        rc_file(Some(OsString::new()), Some(home)),
+        Some(PathBuf::from("/home/user/.config/vvlang/init.vv"))
+    );
+    assert_eq!(rc_file(None, None), None);
+}
+
+#[test]
+fn test_session_multi_line_prompt() {
+    let mut session = Session::new();
+    assert_eq!(session.prompt(), ">>> ");
+
+    assert_eq!(
+        session.process_line("let f = fn() {\n").unwrap(),
+        Flow::Continue
+    );
+    assert_eq!(session.prompt(), "... ");
+
+    assert_eq!(session.process_line("};\n").unwrap(), Flow::Continue);
+    assert_eq!(session.prompt(), ">>> ");
+}
+
+#[test]
+fn test_session_cancel() {
+    let mut session = Session::new();
+    session.process_line("let x = (\n").unwrap();
+
+    session.cancel();
+    assert_eq!(session.prompt(), ">>> ");
+}
+
+#[test_case("exit()\n", true; "Exit call")]
+#[test_case("exit()\r\n", true; "Windows line ending")]
+#[test_case("  quit \t\n", true; "Quit with whitespace")]
+#[test_case("exit", true; "No line ending")]
+#[test_case("exit(1)\n", false; "Exit with arguments")]
+#[test_case("let exit = 1;\n", false; "Other statement")]
+#[test_case("\n", false; "Empty line")]
+fn test_is_exit_command(line: &str, expected: bool) {
+    assert_eq!(is_exit_command(line), expected);
+}
+
+#[test_case("exit()\r\n"; "Exit command")]
+#[test_case(""; "End of input")]
+fn test_session_exit(line: &str) {
+    let mut session = Session::new();
+    assert_eq!(session.process_line(line).unwrap(), Flow::Exit);
+}
+
+#[test]
+fn test_session_end_of_input_inside_multi_line_input() {
+    let mut session = Session::new();
+    session.process_line("let f = fn() {\n").unwrap();
+
+    assert_eq!(session.process_line("").unwrap(), Flow::Exit);
+}
+
+#[test]
+fn test_session_empty_line_does_not_exit() {
+    let mut session = Session::new();
+    assert_eq!(session.process_line("\n").unwrap(), Flow::Continue);
+}
+
+#[test]
+fn test_session_exit_inside_multi_line_input() {
+    let mut session = Session::new();
+    session.process_line("let f = fn() {\n").unwrap();
+
+    // Part of the pending input, not a command
+    assert_eq!(session.process_line("exit()\n").unwrap(), Flow::Continue);
+}
+
+#[test_case(":help", Action::Help; "Help")]
+#[test_case(":quit\n", Action::Quit; "Quit")]
+#[test_case(":env", Action::Env; "Env")]
+#[test_case(":reset", Action::Reset { hard: false }; "Reset")]
+#[test_case(":reset hard", Action::Reset { hard: true }; "Hard reset")]
+#[test_case(":reset soft", Action::Unknown(":reset soft".to_owned()); "Unknown reset")]
+#[test_case(":paste", Action::Paste; "Paste")]
+#[test_case(":tokens", Action::Toggle(View::Tokens); "Toggle tokens")]
+#[test_case(":tokens on", Action::Set(View::Tokens, true); "Tokens on")]
+#[test_case(":tokens off", Action::Set(View::Tokens, false); "Tokens off")]
+#[test_case(":tokens let x = 5;", Action::Show(View::Tokens, "let x = 5;".to_owned()); "Tokens of code")]
+#[test_case("  :ast  ", Action::Toggle(View::Ast); "Toggle ast with whitespace")]
+#[test_case(":ast off\r\n", Action::Set(View::Ast, false); "Ast off")]
+#[test_case(":ast return 1;", Action::Show(View::Ast, "return 1;".to_owned()); "Ast of code")]
+#[test_case(":save out.vv", Action::Save { path: "out.vv".to_owned(), overwrite: false }; "Save")]
+#[test_case(":save! out.vv", Action::Save { path: "out.vv".to_owned(), overwrite: true }; "Save overwriting")]
+#[test_case(":time on", Action::Set(View::Time, true); "Time on")]
+#[test_case(":time let x = 1;", Action::Show(View::Time, "let x = 1;".to_owned()); "Time of code")]
+#[test_case(":frobnicate now", Action::Unknown(":frobnicate".to_owned()); "Unknown command")]
+#[test_case("let x = 5;", Action::Code; "Code")]
+#[test_case("let s = \":help\";", Action::Code; "Code containing a command")]
+fn test_parse_action(line: &str, expected: Action) {
+    assert_eq!(parse_action(line), expected);
+}
+
+#[test]
+fn test_session_views() {
+    let mut session = Session::new();
+    assert!(!session.is_shown(View::Tokens));
+    assert!(session.is_shown(View::Ast));
+
+    session.process_line(":tokens\n").unwrap();
+    session.process_line(":ast off\n").unwrap();
+    assert!(session.is_shown(View::Tokens));
+    assert!(!session.is_shown(View::Ast));
+}
+
+#[test]
+fn test_session_quit_command() {
+    let mut session = Session::new();
+    assert_eq!(session.process_line(":quit\n").unwrap(), Flow::Exit);
+}
+
+#[test]
+fn test_session_command_inside_multi_line_input() {
+    let mut session = Session::new();
+    session.process_line("let f = fn() {\n").unwrap();
+
+    session.process_line(":tokens\n").unwrap();
+    assert!(!session.is_shown(View::Tokens));
+}
+
+/// A path in the temporary directory that is unique to the given test.
+fn temp_path(test_name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("vvlang-{test_name}-{}.vv", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_session_transcript() {
+    let mut session = Session::new();
+    session.process_line("let x = 5;\n").unwrap();
+    session.process_line("let = 10;\n").unwrap();
+    session.process_line(":tokens\n").unwrap();
+    session.process_line("let f = fn() {\n").unwrap();
+    session.process_line("};\n").unwrap();
+    session.process_line(":ast return x;\n").unwrap();
+
+    assert_eq!(
+        session.transcript(),
+        &["let x = 5;\n", "let f = fn() {\n};\n"]
+    );
+}
+
+#[test]
+fn test_session_save() {
+    let path = temp_path("save");
+    let mut session = Session::new();
+    session.process_line("let x = 5;\n").unwrap();
+    session.process_line("let y = 10;\n").unwrap();
+    session.process_line("return y;\n").unwrap();
+
+    session.save(&path, false).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let program: Program = text.parse().unwrap();
+    assert_eq!(program.len(), 3);
+}
+
+#[test]
+fn test_session_save_does_not_overwrite() {
+    let path = temp_path("save-no-overwrite");
+    std::fs::write(&path, "let important = 1;\n").unwrap();
+    let mut session = Session::new();
+    session.process_line("let x = 5;\n").unwrap();
+
+    let error = session.save(&path, false).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "let important = 1;\n"
+    );
+
+    session.save(&path, true).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "let x = 5;\n");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test_case("le", vec!["let"]; "Keyword prefix")]
+#[test_case("let x = tr", vec!["true"]; "Keyword prefix after code")]
+#[test_case("re", vec!["return"]; "Return keyword")]
+#[test_case("let x = co", vec!["counter"]; "Binding")]
+#[test_case("let x = ", vec![]; "Empty prefix")]
+#[test_case(":t", vec!["time", "tokens"]; "Command")]
+#[test_case(":sa", vec!["save", "save!"]; "Command with variants")]
+#[test_case("zz", vec![]; "No match")]
+fn test_complete(line: &str, expected: Vec<&str>) {
+    let bindings = vec!["counter".to_owned()];
+    assert_eq!(complete(line, line.len(), &bindings), expected);
+}
+
+#[test]
+fn test_complete_word_under_cursor() {
+    let line = "let x = le + 1;";
+    assert_eq!(complete(line, "let x = le".len(), &[]), vec!["let"]);
+}
+
+#[test]
+fn test_session_bindings() {
+    let mut session = Session::new();
+    session.process_line("let counter = 0;\n").unwrap();
+    session.process_line("let counter = 1;\n").unwrap();
+    // Not bound, since there are errors
+    session.process_line("let other 5;\n").unwrap();
+
+    assert_eq!(session.bindings(), ["counter".to_owned()]);
+    assert_eq!(complete("cou", 3, session.bindings()), vec!["counter"]);
+}
+
+#[test]
+fn test_session_reset() {
+    let mut session = Session::new();
+    session.process_line("let x = 5;\n").unwrap();
+    session.process_line("let y = 10;\n").unwrap();
+    session.process_line(":ast off\n").unwrap();
+
+    assert_eq!(session.reset(false), 2);
+    assert!(session.bindings().is_empty());
+    assert_eq!(session.transcript().len(), 2);
+    assert!(!session.is_shown(View::Ast));
+
+    session.process_line("let z = 1;\n").unwrap();
+    assert_eq!(session.bindings(), ["z".to_owned()]);
+    assert_eq!(session.transcript().len(), 3);
+}
+
+#[test]
+fn test_session_hard_reset() {
+    let mut session = Session::new();
+    session.process_line("let x = 5;\n").unwrap();
+    session.process_line(":reset hard\n").unwrap();
+
+    assert!(session.bindings().is_empty());
+    assert!(session.transcript().is_empty());
+}
+
+#[test]
+fn test_session_load_rc() {
+    let path = temp_path("rc");
+    std::fs::write(&path, "let double = 2;\nlet triple = 3;\n").unwrap();
+    let mut session = Session::new();
+
+    assert!(session.load_rc(&path));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        session.bindings(),
+        ["double".to_owned(), "triple".to_owned()]
+    );
+    assert!(session.transcript().is_empty());
+}
+
+#[test_case("rc-empty", ""; "Empty")]
+#[test_case("rc-blank", "\n  \n"; "Blank lines")]
+#[test_case("rc-comment", "// Nothing yet\n"; "Only a comment")]
+fn test_session_load_empty_rc(name: &str, content: &str) {
+    let path = temp_path(name);
+    std::fs::write(&path, content).unwrap();
+    let mut session = Session::new();
+
+    assert!(session.load_rc(&path));
+    std::fs::remove_file(&path).unwrap();
+    assert!(session.bindings().is_empty());
+    assert!(session.transcript().is_empty());
+}
+
+#[test]
+fn test_session_load_missing_rc() {
+    let mut session = Session::new();
+    assert!(!session.load_rc(&temp_path("missing-rc")));
+    assert!(session.bindings().is_empty());
+}
+
+#[test]
+fn test_session_load_rc_with_errors() {
+    let path = temp_path("rc-errors");
+    std::fs::write(&path, "let double = 2;\nlet triple 3;\n").unwrap();
+    let mut session = Session::new();
+
+    assert!(!session.load_rc(&path));
+    std::fs::remove_file(&path).unwrap();
+    assert!(session.bindings().is_empty());
+
+    // The session is still usable
+    assert_eq!(
+        session.process_line("let x = 1;\n").unwrap(),
+        Flow::Continue
+    );
+    assert_eq!(session.bindings(), ["x".to_owned()]);
+}
+
+#[test]
+fn test_session_paste() {
+    let mut session = Session::new();
+    session.process_line(":paste\n").unwrap();
+    assert!(session.is_pasting());
+    assert_eq!(session.prompt(), "| ");
+
+    // Nothing is parsed until the end of the paste, even a complete statement
+    session.process_line("let x = 1;\n").unwrap();
+    session.process_line("\n").unwrap();
+    session.process_line("let y = (x +\n").unwrap();
+    assert!(session.transcript().is_empty());
+    session.process_line("  2);\n").unwrap();
+    session.process_line(":end\n").unwrap();
+
+    assert!(!session.is_pasting());
+    assert_eq!(
+        session.transcript(),
+        &["let x = 1;\n\nlet y = (x +\n  2);\n"]
+    );
+    assert_eq!(session.bindings(), ["x".to_owned(), "y".to_owned()]);
+}
+
+#[test]
+fn test_session_paste_end_of_input() {
+    let mut session = Session::new();
+    session.process_line(":paste\n").unwrap();
+    session.process_line("let x = 1;\n").unwrap();
+
+    // Ending the input finishes the paste rather than leaving the REPL
+    assert_eq!(session.process_line("").unwrap(), Flow::Continue);
+    assert!(!session.is_pasting());
+    assert_eq!(session.transcript(), &["let x = 1;\n"]);
+}
+
+#[test]
+fn test_session_paste_commands_are_text() {
+    let mut session = Session::new();
+    session.process_line(":paste\n").unwrap();
+    session.process_line(":paste\n").unwrap();
+    session.process_line(":tokens\n").unwrap();
+    assert_eq!(session.process_line("quit\n").unwrap(), Flow::Continue);
+    session.finish_paste().unwrap();
+
+    assert!(!session.is_pasting());
+    assert!(!session.is_shown(View::Tokens));
+    // The pasted text doesn't parse, so it's not kept
+    assert!(session.transcript().is_empty());
+}
+
+#[test]
+fn test_session_cancel_paste() {
+    let mut session = Session::new();
+    session.process_line(":paste\n").unwrap();
+    session.process_line("let x = 1;\n").unwrap();
+    session.cancel();
+
+    assert!(!session.is_pasting());
+    session.process_line(":end\n").unwrap();
+    assert!(session.transcript().is_empty());
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use crate::timings::{count_tokens, Stage, Timings};
+
+#[test]
+fn test_measure() {
+    let mut timings = Timings::new();
+
+    let tokens = timings.measure("lex", "tokens", || vec!["let", "x"], |tokens| tokens.len());
+
+    assert_eq!(tokens, vec!["let", "x"]);
+    assert_eq!(timings.stages.len(), 1);
+    assert_eq!(timings.stages[0].name, "lex");
+    assert_eq!(timings.stages[0].count, 2);
+    assert_eq!(timings.stages[0].unit, "tokens");
+}
+
+#[test]
+fn test_report() {
+    let timings = Timings {
+        stages: vec![
+            Stage {
+                name: "lex",
+                duration: Duration::from_micros(12_340),
+                count: 48210,
+                unit: "tokens",
+            },
+            Stage {
+                name: "parse",
+                duration: Duration::from_millis(3),
+                count: 12,
+                unit: "statements",
+            },
+        ],
+    };
+
+    assert_eq!(
+        timings.report(),
+        "lex: 12.3ms (48210 tokens)\nparse: 3.0ms (12 statements)\n"
+    );
+}
+
+#[test]
+fn test_report_empty() {
+    assert_eq!(Timings::new().report(), "");
+}
+
+#[test]
+fn test_count_tokens() {
+    // let, x, =, 5, ;, EOF
+    assert_eq!(count_tokens("let x = 5;"), 6);
+}
@@ -0,0 +1,213 @@
+use std::str::FromStr;
+
+use crate::core::lexer::Lexer;
+#[cfg(feature = "serde")]
+use crate::core::tokens::Token;
+use crate::core::tokens::{TokenType, UnknownTokenType};
+
+use test_case::test_case;
+
+const ALL_TOKEN_TYPES: [TokenType; 40] = [
+    TokenType::Illegal,
+    TokenType::EOF,
+    TokenType::Ident,
+    TokenType::Int,
+    TokenType::Assign,
+    TokenType::Eq,
+    TokenType::NotEq,
+    TokenType::Plus,
+    TokenType::Minus,
+    TokenType::Bang,
+    TokenType::Asterisk,
+    TokenType::Slash,
+    TokenType::Lt,
+    TokenType::Gt,
+    TokenType::And,
+    TokenType::Or,
+    TokenType::PlusAssign,
+    TokenType::MinusAssign,
+    TokenType::AsteriskAssign,
+    TokenType::SlashAssign,
+    TokenType::Comma,
+    TokenType::Semicolon,
+    TokenType::LParen,
+    TokenType::RParen,
+    TokenType::LBrace,
+    TokenType::RBrace,
+    TokenType::LBracket,
+    TokenType::RBracket,
+    TokenType::Function,
+    TokenType::Let,
+    TokenType::True,
+    TokenType::False,
+    TokenType::If,
+    TokenType::Else,
+    TokenType::Elif,
+    TokenType::Return,
+    TokenType::Indent,
+    TokenType::Dedent,
+    TokenType::Comment,
+    TokenType::NewLine,
+];
+
+/// Fails to compile when a variant is added to ``TokenType``,
+/// as a reminder to add it to ``ALL_TOKEN_TYPES`` too.
+#[allow(dead_code)]
+fn all_token_types_are_listed(t: TokenType) {
+    match t {
+        TokenType::Illegal
+        | TokenType::EOF
+        | TokenType::Ident
+        | TokenType::Int
+        | TokenType::Assign
+        | TokenType::Eq
+        | TokenType::NotEq
+        | TokenType::Plus
+        | TokenType::Minus
+        | TokenType::Bang
+        | TokenType::Asterisk
+        | TokenType::Slash
+        | TokenType::Lt
+        | TokenType::Gt
+        | TokenType::And
+        | TokenType::Or
+        | TokenType::PlusAssign
+        | TokenType::MinusAssign
+        | TokenType::AsteriskAssign
+        | TokenType::SlashAssign
+        | TokenType::Comma
+        | TokenType::Semicolon
+        | TokenType::LParen
+        | TokenType::RParen
+        | TokenType::LBrace
+        | TokenType::RBrace
+        | TokenType::LBracket
+        | TokenType::RBracket
+        | TokenType::Function
+        | TokenType::Let
+        | TokenType::True
+        | TokenType::False
+        | TokenType::If
+        | TokenType::Else
+        | TokenType::Elif
+        | TokenType::Return
+        | TokenType::Indent
+        | TokenType::Dedent
+        | TokenType::Comment
+        | TokenType::NewLine => {}
+    }
+}
+
+#[test]
+fn test_token_type_round_trip() {
+    for token_type in ALL_TOKEN_TYPES {
+        let name = format!("{token_type:?}");
+        assert_eq!(TokenType::from_str(&name), Ok(token_type));
+    }
+}
+
+#[test_case("Plus", TokenType::Plus; "Operator")]
+#[test_case("EOF", TokenType::EOF; "Upper case")]
+fn test_token_type_from_str(name: &str, expected: TokenType) {
+    assert_eq!(name.parse::<TokenType>(), Ok(expected));
+}
+
+#[test_case("plus"; "Wrong case")]
+#[test_case("+"; "Display form")]
+#[test_case(""; "Empty")]
+fn test_token_type_from_str_error(name: &str) {
+    assert_eq!(
+        name.parse::<TokenType>(),
+        Err(UnknownTokenType(name.to_owned()))
+    );
+}
+
+#[test]
+fn test_is_assignment_op() {
+    let assignment_ops: Vec<TokenType> = ALL_TOKEN_TYPES
+        .into_iter()
+        .filter(TokenType::is_assignment_op)
+        .collect();
+
+    assert_eq!(
+        assignment_ops,
+        vec![
+            TokenType::Assign,
+            TokenType::PlusAssign,
+            TokenType::MinusAssign,
+            TokenType::AsteriskAssign,
+            TokenType::SlashAssign,
+        ]
+    );
+}
+
+#[test]
+fn test_symbol() {
+    let with_symbol: Vec<TokenType> = ALL_TOKEN_TYPES
+        .into_iter()
+        .filter(|t| t.symbol().is_some())
+        .collect();
+    assert_eq!(with_symbol.len(), 24);
+
+    for token_type in with_symbol {
+        let symbol = token_type.symbol().unwrap();
+        // Lexing the symbol gives back the same token type
+        let mut lexer = Lexer::new(symbol).unwrap();
+        assert_eq!(lexer.next_token().r#type, token_type, "{symbol}");
+        assert_eq!(lexer.next_token().r#type, TokenType::EOF, "{symbol}");
+    }
+}
+
+#[test_case(TokenType::Ident; "Identifier")]
+#[test_case(TokenType::Int; "Integer")]
+#[test_case(TokenType::EOF; "End of file")]
+#[test_case(TokenType::Illegal; "Illegal")]
+#[test_case(TokenType::NewLine; "New line")]
+#[test_case(TokenType::Indent; "Indent")]
+#[test_case(TokenType::Let; "Keyword")]
+fn test_symbol_none(token_type: TokenType) {
+    assert_eq!(token_type.symbol(), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_token_type_serialized_as_name() {
+    let json = serde_json::to_string(&TokenType::PlusAssign).unwrap();
+    assert_eq!(json, "\"PlusAssign\"");
+    assert_eq!(
+        serde_json::from_str::<TokenType>(&json).unwrap(),
+        TokenType::PlusAssign
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_tokens_serde_round_trip() {
+    let source = include_str!("../../tests/fixtures/highlight/sample.vv");
+    let mut lexer = Lexer::new(source).unwrap();
+    let tokens: Vec<Token> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token)
+    })
+    .collect();
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let deserialized: Vec<Token> = serde_json::from_str(&json).unwrap();
+
+    // Tokens are compared by type and literal only, so check every field
+    let fields = |token: &Token| {
+        (
+            token.r#type.clone(),
+            token.literal.clone(),
+            token.start,
+            token.end,
+            token.line,
+            token.char_offset,
+            token.value,
+        )
+    };
+    assert_eq!(
+        deserialized.iter().map(fields).collect::<Vec<_>>(),
+        tokens.iter().map(fields).collect::<Vec<_>>()
+    );
+}
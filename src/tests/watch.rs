@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use notify::event::{AccessKind, CreateKind, DataChange, EventKind, ModifyKind, RemoveKind};
+use notify::Event;
+use test_case::test_case;
+
+use crate::cli::Input;
+use crate::watch::{files_to_watch, touches, Debouncer};
+
+#[test]
+fn test_files_to_watch() {
+    let inputs = vec![
+        Input::File("a.vv".to_owned()),
+        Input::Stdin,
+        Input::File("b.vv".to_owned()),
+        Input::File("a.vv".to_owned()),
+    ];
+
+    assert_eq!(
+        files_to_watch(&inputs),
+        vec![PathBuf::from("a.vv"), PathBuf::from("b.vv")]
+    );
+}
+
+#[test]
+fn test_files_to_watch_stdin_only() {
+    assert!(files_to_watch(&[Input::Stdin]).is_empty());
+}
+
+#[test_case(EventKind::Modify(ModifyKind::Data(DataChange::Content)), "/scripts/a.vv", true; "Written")]
+#[test_case(EventKind::Create(CreateKind::File), "/scripts/a.vv", true; "Replaced by an editor")]
+#[test_case(EventKind::Remove(RemoveKind::File), "/scripts/a.vv", true; "Removed")]
+#[test_case(EventKind::Access(AccessKind::Any), "/scripts/a.vv", false; "Only read")]
+#[test_case(EventKind::Modify(ModifyKind::Data(DataChange::Content)), "/scripts/b.vv", false; "Another file in the same directory")]
+fn test_touches(kind: EventKind, path: &str, expected: bool) {
+    let event = Event::new(kind).add_path(PathBuf::from(path));
+    assert_eq!(touches(&event, &[PathBuf::from("/scripts/a.vv")]), expected);
+}
+
+#[test]
+fn test_debouncer_without_changes() {
+    let mut debouncer = Debouncer::new(Duration::from_millis(200));
+    assert!(!debouncer.settled(Instant::now()));
+}
+
+#[test]
+fn test_debouncer_single_change() {
+    let start = Instant::now();
+    let mut debouncer = Debouncer::new(Duration::from_millis(200));
+
+    debouncer.change(start);
+    assert!(!debouncer.settled(start + Duration::from_millis(100)));
+    assert!(debouncer.settled(start + Duration::from_millis(200)));
+    // Reported only once
+    assert!(!debouncer.settled(start + Duration::from_millis(300)));
+}
+
+#[test]
+fn test_debouncer_burst_of_changes() {
+    let start = Instant::now();
+    let mut debouncer = Debouncer::new(Duration::from_millis(200));
+
+    // EG: an editor writing the file twice
+    debouncer.change(start);
+    debouncer.change(start + Duration::from_millis(150));
+    assert!(!debouncer.settled(start + Duration::from_millis(250)));
+    assert!(debouncer.settled(start + Duration::from_millis(350)));
+}
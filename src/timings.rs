@@ -0,0 +1,86 @@
+//! Instrumentation of the stages of the pipeline, for `--time` and `:time`.
+
+use std::time::{Duration, Instant};
+
+use vvlang::core::lexer::Lexer;
+use vvlang::core::tokens::TokenType;
+
+/// How long a stage of the pipeline took, and how much it produced
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub name: &'static str,
+    pub duration: Duration,
+    /// How many items (EG: tokens) the stage produced
+    pub count: usize,
+    /// What the items are called, in plural
+    pub unit: &'static str,
+}
+
+/// The stages measured so far, in order
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    pub stages: Vec<Stage>,
+}
+
+impl Timings {
+    pub fn new() -> Timings {
+        Timings::default()
+    }
+
+    /// Run ``stage``, recording how long it took under ``name``, together
+    /// with the number of ``unit``s it produced, as told by ``count``.
+    pub fn measure<T>(
+        &mut self,
+        name: &'static str,
+        unit: &'static str,
+        stage: impl FnOnce() -> T,
+        count: impl FnOnce(&T) -> usize,
+    ) -> T {
+        let start = Instant::now();
+        let result = stage();
+        let duration = start.elapsed();
+
+        self.stages.push(Stage {
+            name,
+            duration,
+            count: count(&result),
+            unit,
+        });
+        result
+    }
+
+    /// One line per stage, EG: "lex: 12.3ms (48210 tokens)".
+    pub fn report(&self) -> String {
+        self.stages
+            .iter()
+            .map(|stage| {
+                format!(
+                    "{}: {:.1}ms ({} {})\n",
+                    stage.name,
+                    stage.duration.as_secs_f64() * 1000.0,
+                    stage.count,
+                    stage.unit
+                )
+            })
+            .collect()
+    }
+}
+
+/// Lex the whole ``source``, only to count its tokens (EOF included).
+pub fn count_tokens(source: &str) -> usize {
+    let Ok(mut lexer) = Lexer::new(source) else {
+        return 0;
+    };
+
+    let mut num_tokens = 0;
+    loop {
+        num_tokens += 1;
+        if lexer.next_token().r#type == TokenType::EOF {
+            return num_tokens;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/timings.rs"]
+mod timings_tests;
@@ -0,0 +1,153 @@
+//! `--watch`: run the scripts again whenever they change on disk.
+//! The directories of the scripts are watched rather than the scripts
+//! themselves, since editors often save by replacing the file,
+//! which would silently end a watch on the old one.
+
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::cli::Input;
+
+/// How long to wait for filesystem events before checking
+/// whether the changes received so far have settled
+const TICK: Duration = Duration::from_millis(50);
+
+/// How long the files need to stay untouched before we run them again,
+/// so that editors writing a file several times in a row only trigger
+/// a single run.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+
+/// The files that need to be watched to run ``inputs`` again,
+/// in order and without duplicates.
+/// Stdin can't be watched, so it's left out.
+// TODO: include the files imported by the scripts, once there are imports
+pub fn files_to_watch(inputs: &[Input]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        if let Input::File(path) = input {
+            let path = PathBuf::from(path);
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Collapses a burst of changes into a single one, reported once
+/// no other change has happened for a while.
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    /// When the last change not reported yet happened
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Debouncer {
+        Debouncer {
+            delay,
+            last_change: None,
+        }
+    }
+
+    /// Record that something changed at ``now``.
+    pub fn change(&mut self, now: Instant) {
+        self.last_change = Some(now);
+    }
+
+    /// Whether the changes recorded so far have settled at ``now``,
+    /// in which case they're not reported again.
+    pub fn settled(&mut self, now: Instant) -> bool {
+        match self.last_change {
+            Some(last_change) if now.duration_since(last_change) >= self.delay => {
+                self.last_change = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The absolute version of ``path``, so that it can be compared with
+/// the paths of the events, falling back to ``path`` itself if it can't
+/// be resolved (EG: while an editor replaces it).
+fn absolute(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_owned())
+}
+
+/// Whether ``event`` changed the content of one of ``files``,
+/// which are expected to be absolute.
+pub fn touches(event: &notify::Event, files: &[PathBuf]) -> bool {
+    let changed = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    changed
+        && event
+            .paths
+            .iter()
+            .any(|path| files.contains(&absolute(path)))
+}
+
+/// Call ``run`` right away, then again every time one of ``files`` changes,
+/// until the process is interrupted (EG: with Ctrl-C).
+/// The screen is cleared before each run, when printing to a terminal.
+pub fn watch(files: &[PathBuf], mut run: impl FnMut()) -> eyre::Result<()> {
+    let watched = match files {
+        [file] => format!("'{}'", file.display()),
+        files => format!("{} files", files.len()),
+    };
+
+    let files: Vec<PathBuf> = files.iter().map(|file| absolute(file)).collect();
+    let mut directories: Vec<&Path> = Vec::new();
+    for file in &files {
+        let directory = file.parent().unwrap_or(file);
+        if !directories.contains(&directory) {
+            directories.push(directory);
+        }
+    }
+
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for directory in directories {
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+    }
+
+    let mut debouncer = Debouncer::new(DEBOUNCE_DELAY);
+    run();
+    eprintln!("\nWatching {watched} for changes, press Ctrl-C to stop");
+
+    loop {
+        match events.recv_timeout(TICK) {
+            Ok(event) => {
+                if touches(&event?, &files) {
+                    debouncer.change(Instant::now());
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                eyre::bail!("Stopped receiving changes to {watched}")
+            }
+        }
+
+        if debouncer.settled(Instant::now()) {
+            if std::io::stdout().is_terminal() {
+                print!("\x1b[2J\x1b[H");
+            }
+            run();
+            eprintln!("\nWatching {watched} for changes, press Ctrl-C to stop");
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/watch.rs"]
+mod watch_tests;
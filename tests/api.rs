@@ -0,0 +1,140 @@
+//! Use the library only through its public API, to lock in what's exported.
+
+use vvlang::diagnostics::FileDiagnostic;
+use vvlang::highlight::HighlightFormat;
+use vvlang::{
+    Lexer, LexerConfig, Parser, ParserErrorKind, Program, Span, Statement, Token, TokenType,
+};
+
+#[test]
+fn test_lexer() {
+    let mut lexer = Lexer::new("let x = 5;").unwrap();
+    let tokens: Vec<Token> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token)
+    })
+    .collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::new(TokenType::Let, "let"),
+            Token::new(TokenType::Ident, "x"),
+            Token::new(TokenType::Assign, "="),
+            Token::new(TokenType::Int, "5"),
+            Token::new(TokenType::Semicolon, ";"),
+        ]
+    );
+    assert_eq!(tokens[3].value, Some(5));
+    assert_eq!((tokens[1].start, tokens[1].end), (4, 5));
+}
+
+#[test]
+fn test_lexer_with_config() {
+    let config = LexerConfig {
+        comment_prefix: "#".to_owned(),
+        ..LexerConfig::default()
+    };
+    let mut lexer = Lexer::with_config("# just a comment", config).unwrap();
+    assert_eq!(lexer.next_token().r#type, TokenType::EOF);
+}
+
+#[test]
+fn test_parser() {
+    let mut parser = Parser::new("let x = 5;\nreturn x;\n").unwrap();
+    let program = parser.parse_program();
+
+    assert!(parser.errors.is_empty());
+    assert_eq!(program.len(), 2);
+    let Statement::Assignment(let_statement) = &program.statements()[0] else {
+        panic!(
+            "Expected a let statement, found {:?}",
+            program.statements()[0]
+        );
+    };
+    assert_eq!(let_statement.identifier.name, "x");
+    assert_eq!(let_statement.value.span, Span::new(8, 9));
+    assert!(matches!(program.statements()[1], Statement::Return(_)));
+}
+
+#[test]
+fn test_ast_module() {
+    let program: vvlang::core::ast::Program = "return 1;".parse().unwrap();
+    let vvlang::core::ast::Statement::Return(statement) = &program.statements()[0] else {
+        panic!(
+            "Expected a return statement, found {:?}",
+            program.statements()[0]
+        );
+    };
+    assert_eq!(statement.value.node.literal(), "1");
+}
+
+#[test]
+fn test_parser_errors() {
+    let errors = "let = 5;".parse::<Program>().unwrap_err();
+    assert!(matches!(
+        &*errors[0].kind,
+        ParserErrorKind::UnexpectedToken { expected, found }
+            if expected == &[TokenType::Ident] && found.r#type == TokenType::Assign
+    ));
+    assert_eq!((errors[0].line_num, errors[0].char_offset), (1, 4));
+
+    let diagnostic = FileDiagnostic::from_parser_error("script.vv", &errors[0]);
+    assert_eq!(diagnostic.code, "unexpected-token");
+}
+
+#[test]
+fn test_tools() {
+    assert_eq!(
+        vvlang::formatter::format_source("let   x=5 ;").unwrap(),
+        "let x = 5;\n"
+    );
+    assert!(
+        vvlang::highlight::highlight("let x = 5;", HighlightFormat::Html)
+            .starts_with("<pre class=\"vvlang\">")
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip_keeps_positions() {
+    let mut lexer = Lexer::new("let x = 5;\nreturn x;\n").unwrap();
+    let tokens: Vec<Token> = std::iter::from_fn(|| {
+        let token = lexer.next_token();
+        (token.r#type != TokenType::EOF).then_some(token)
+    })
+    .collect();
+    let json = serde_json::to_string(&tokens).unwrap();
+    let deserialized: Vec<Token> = serde_json::from_str(&json).unwrap();
+
+    // Token equality ignores positions, so compare them explicitly
+    let positions = |tokens: &[Token]| -> Vec<(usize, usize, usize, usize)> {
+        tokens
+            .iter()
+            .map(|t| (t.start, t.end, t.line, t.char_offset))
+            .collect()
+    };
+    assert_eq!(positions(&deserialized), positions(&tokens));
+    assert_eq!(deserialized[6].start, 11);
+    assert_eq!((deserialized[6].line, deserialized[6].char_offset), (2, 0));
+
+    // Same for spans, which node equality ignores too
+    let program: Program = "let x = 5;\nreturn x;\n".parse().unwrap();
+    let json = serde_json::to_value(&program).unwrap();
+    let spans: Vec<&serde_json::Value> = json["statements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|statement| {
+            let (_, node) = statement.as_object().unwrap().iter().next().unwrap();
+            &node["value"]["span"]
+        })
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            &serde_json::json!({"start": 8, "end": 9}),
+            &serde_json::json!({"start": 18, "end": 19}),
+        ]
+    );
+}
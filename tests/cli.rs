@@ -0,0 +1,706 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Output, Stdio};
+
+/// Run the vvlang binary with the given arguments.
+fn run_vvlang(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vvz-lang"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("Failed to run the vvlang binary")
+}
+
+/// Run the vvlang binary with the given arguments, piping ``input`` to its stdin.
+fn run_vvlang_with_stdin(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_vvz-lang"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run the vvlang binary");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_run_file() {
+    let output = run_vvlang(&["tests/fixtures/valid.vv"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_run_file_parse_error() {
+    let output = run_vvlang(&["tests/fixtures/parse_error.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("tests/fixtures/parse_error.vv:2:5: error: Expected identifier, found '='"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_run_missing_file() {
+    let output = run_vvlang(&["tests/fixtures/does_not_exist.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("Could not read 'tests/fixtures/does_not_exist.vv'"),
+        "Unexpected stderr: {stderr}"
+    );
+    // A clean error, not an eyre report
+    assert!(!stderr.contains("Location:"), "Unexpected stderr: {stderr}");
+}
+
+#[test]
+fn test_run_piped_stdin() {
+    let output = run_vvlang_with_stdin(&[], "let x = 5;\nreturn x;\n");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_run_piped_stdin_parse_error() {
+    let output = run_vvlang_with_stdin(&[], "let x = 5;\nlet = 10;\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("<stdin>:2:5: error: Expected identifier, found '='"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_run_explicit_stdin() {
+    let output = run_vvlang_with_stdin(&["-"], "let = 10;\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("<stdin>:1:"), "Unexpected stderr: {stderr}");
+}
+
+#[test]
+fn test_tokens_mode() {
+    let output = run_vvlang(&["--tokens", "tests/fixtures/valid.vv"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "Let \"let\"");
+    assert_eq!(lines[1], "Ident \"x\"");
+    assert_eq!(lines[5], "NewLine \"\\n\"");
+    assert_eq!(lines.last(), Some(&"EOF \"\""));
+}
+
+#[test]
+fn test_ast_mode() {
+    let output = run_vvlang(&["--ast", "tests/fixtures/valid.vv"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(stdout, "let x = 5;\nlet y = 10;\nreturn y;\n");
+}
+
+#[test]
+fn test_ast_mode_parse_error() {
+    let output = run_vvlang(&["--ast", "tests/fixtures/parse_error.vv"]);
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_run_mode() {
+    let output = run_vvlang(&["--run", "tests/fixtures/valid.vv"]);
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_modes_with_stdin() {
+    let output = run_vvlang_with_stdin(&["--ast"], "return 1;");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(stdout, "return 1;\n");
+}
+
+#[test]
+fn test_mutually_exclusive_modes() {
+    let output = run_vvlang(&["--ast", "--tokens", "tests/fixtures/valid.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("Usage:"), "Unexpected stderr: {stderr}");
+}
+
+#[test]
+fn test_check_mode() {
+    let output = run_vvlang(&[
+        "--check",
+        "tests/fixtures/parse_error.vv",
+        "tests/fixtures/valid.vv",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(
+        stderr.contains("tests/fixtures/parse_error.vv:2:"),
+        "Unexpected stderr: {stderr}"
+    );
+    // Both files were processed, even though the first one is broken
+    assert!(
+        stderr.contains("2 files checked, 1 with errors, "),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_check_mode_clean() {
+    let output = run_vvlang(&["--check", "tests/fixtures/valid.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success());
+    assert!(
+        stderr.contains("1 file checked, 0 with errors, 0 errors total"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_check_mode_last_token_without_semicolon() {
+    let output = run_vvlang_with_stdin(&["--check", "--no-color", "-"], "let x = 5;\nx");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("<stdin>:2:1: error: Unsupported token: 'x'"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_empty_input() {
+    let output = run_vvlang_with_stdin(&["--check", "-"], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("0 errors total"));
+
+    let output = run_vvlang_with_stdin(&["--ast", "-"], "");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_empty_input_json() {
+    let output = run_vvlang_with_stdin(&["--ast", "--format", "json", "-"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout_json(&output), serde_json::json!({"statements": []}));
+}
+
+#[cfg(not(feature = "serde"))]
+#[test]
+fn test_json_without_serde() {
+    for args in [
+        ["--tokens", "--format", "json", "tests/fixtures/valid.vv"],
+        [
+            "--check",
+            "--diagnostics",
+            "json",
+            "tests/fixtures/valid.vv",
+        ],
+    ] {
+        // The arguments are rejected before reading any input
+        let output = run_vvlang(&args);
+
+        assert_eq!(output.status.code(), Some(64));
+        assert!(output.stdout.is_empty());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("built without the 'serde' feature"),
+            "Unexpected stderr for {args:?}"
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+/// Parse the JSON printed on stdout by the binary.
+fn stdout_json(output: &Output) -> serde_json::Value {
+    serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON")
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_tokens_mode_json() {
+    let output = run_vvlang(&["--tokens", "--format", "json", "tests/fixtures/valid.vv"]);
+    assert!(output.status.success());
+
+    let tokens = stdout_json(&output);
+    let tokens = tokens.as_array().unwrap();
+    assert_eq!(tokens[0]["type"], "Let");
+    assert_eq!(tokens[1]["literal"], "x");
+    assert_eq!(tokens[1]["start"], 4);
+    assert_eq!(tokens.last().unwrap()["type"], "EOF");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_ast_mode_json() {
+    let output = run_vvlang(&[
+        "--ast",
+        "--format",
+        "json",
+        "--pretty",
+        "tests/fixtures/valid.vv",
+    ]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\n  "));
+
+    let program = stdout_json(&output);
+    let statements = program["statements"].as_array().unwrap();
+    assert_eq!(statements.len(), 3);
+    assert_eq!(statements[0]["Assignment"]["identifier"]["name"], "x");
+    assert_eq!(
+        statements[2]["Return"]["value"]["node"]["tokens"][0]["literal"],
+        "y"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_ast_mode_json_parse_error() {
+    let output = run_vvlang(&["--ast", "--format", "json", "tests/fixtures/parse_error.vv"]);
+    assert!(!output.status.success());
+
+    let report = stdout_json(&output);
+    let error = &report["errors"][0];
+    assert_eq!(error["line"], 2);
+    assert_eq!(error["column"], 5);
+    assert_eq!(error["message"], "Expected identifier, found '='");
+    assert_eq!(error["code"], "unexpected-token");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_mode_missing_file() {
+    let output = run_vvlang(&["--tokens", "--format", "json", "tests/fixtures/missing.vv"]);
+    assert!(!output.status.success());
+
+    let report = stdout_json(&output);
+    assert_eq!(report["errors"][0]["code"], "io-error");
+    assert!(report["errors"][0]["line"].is_null());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_check_mode_json_diagnostics() {
+    let output = run_vvlang(&[
+        "--check",
+        "--diagnostics",
+        "json",
+        "tests/fixtures/diagnostics/two_errors.vv",
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = std::fs::read_to_string("tests/fixtures/diagnostics/two_errors.jsonl").unwrap();
+    assert_eq!(stdout, expected);
+
+    let diagnostics: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be valid JSON"))
+        .collect();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0]["code"], "mismatched-delimiter");
+    assert_eq!(diagnostics[1]["start_line"], 3);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_check_mode_json_diagnostics_missing_file() {
+    let output = run_vvlang(&[
+        "--check",
+        "--diagnostics",
+        "json",
+        "tests/fixtures/missing.vv",
+        "tests/fixtures/valid.vv",
+    ]);
+    assert_eq!(output.status.code(), Some(66));
+
+    let diagnostic = stdout_json(&output);
+    assert_eq!(diagnostic["file"], "tests/fixtures/missing.vv");
+    assert_eq!(diagnostic["code"], "io-error");
+    assert!(diagnostic["start_line"].is_null());
+}
+
+#[test]
+fn test_emit_dot() {
+    let output = run_vvlang_with_stdin(&["--emit", "dot"], "let x = 1 + 2;\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(
+        stdout.starts_with("digraph ast {\n"),
+        "Unexpected stdout: {stdout}"
+    );
+    assert!(
+        stdout.contains("[label=\"Identifier: x\"];"),
+        "Unexpected stdout: {stdout}"
+    );
+}
+
+#[test]
+fn test_color_always() {
+    let output = run_vvlang(&["--color", "always", "tests/fixtures/parse_error.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        stderr.contains("\x1b[1;31merror\x1b[0m"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_no_color_when_piped() {
+    let output = run_vvlang(&["tests/fixtures/parse_error.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!stderr.contains('\x1b'), "Unexpected stderr: {stderr}");
+}
+
+#[test]
+fn test_no_color_flag() {
+    let output = run_vvlang(&[
+        "--color",
+        "always",
+        "--no-color",
+        "tests/fixtures/parse_error.vv",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        stderr.contains("error: Expected identifier"),
+        "Unexpected stderr: {stderr}"
+    );
+    assert!(!stderr.contains('\x1b'), "Unexpected stderr: {stderr}");
+}
+
+#[test]
+fn test_exit_codes() {
+    let cases: [(&[&str], i32); 8] = [
+        (&["tests/fixtures/valid.vv"], 0),
+        (&["--ast", "tests/fixtures/valid.vv"], 0),
+        (&["tests/fixtures/parse_error.vv"], 2),
+        (
+            &[
+                "--check",
+                "tests/fixtures/valid.vv",
+                "tests/fixtures/parse_error.vv",
+            ],
+            2,
+        ),
+        (&["--frobnicate"], 64),
+        (&["--tokens", "--ast"], 64),
+        (&["tests/fixtures/does_not_exist.vv"], 66),
+        (
+            &[
+                "--check",
+                "tests/fixtures/parse_error.vv",
+                "tests/fixtures/does_not_exist.vv",
+            ],
+            66,
+        ),
+    ];
+
+    for (args, expected_code) in cases {
+        let output = run_vvlang(args);
+        assert_eq!(
+            output.status.code(),
+            Some(expected_code),
+            "Arguments: {args:?}"
+        );
+    }
+}
+
+#[test]
+fn test_multiple_files() {
+    let output = run_vvlang(&[
+        "--ast",
+        "tests/fixtures/valid.vv",
+        "tests/fixtures/valid_2.vv",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout,
+        "let x = 5;\nlet y = 10;\nreturn y;\nlet z = 1;\nreturn z;\n"
+    );
+}
+
+#[test]
+fn test_multiple_files_stop_at_first_failure() {
+    let output = run_vvlang(&[
+        "--ast",
+        "tests/fixtures/valid.vv",
+        "tests/fixtures/parse_error.vv",
+        "tests/fixtures/valid_2.vv",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(2));
+    // The first file ran, the last one didn't
+    assert_eq!(stdout, "let x = 5;\nlet y = 10;\nreturn y;\n");
+    assert!(
+        stderr.contains("tests/fixtures/parse_error.vv:2:5: error:"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_check_mode_multiple_files_after_failure() {
+    let output = run_vvlang(&[
+        "--check",
+        "tests/fixtures/valid.vv",
+        "tests/fixtures/parse_error.vv",
+        "tests/fixtures/valid_2.vv",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        stderr.contains("3 files checked, 1 with errors, "),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_time() {
+    let output = run_vvlang(&["--time", "tests/fixtures/valid.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success());
+    assert!(stderr.starts_with("lex: "), "Unexpected stderr: {stderr}");
+    assert!(
+        stderr.contains("ms (17 tokens)\n"),
+        "Unexpected stderr: {stderr}"
+    );
+    assert!(stderr.contains("\nparse: "), "Unexpected stderr: {stderr}");
+    assert!(
+        stderr.contains("ms (3 statements)\n"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_no_time_by_default() {
+    let output = run_vvlang(&["tests/fixtures/valid.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!stderr.contains("lex: "), "Unexpected stderr: {stderr}");
+    assert!(!stderr.contains("parse: "), "Unexpected stderr: {stderr}");
+}
+
+#[test]
+fn test_silent_by_default() {
+    let output = run_vvlang(&["tests/fixtures/valid.vv"]);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+}
+
+#[test]
+fn test_very_verbose_logs_tokens_and_statements() {
+    let output = run_vvlang(&["-vv", "tests/fixtures/valid.vv"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success());
+    assert!(
+        stderr.contains("[TRACE vvlang::core::lexer] Let \"let\" at 0..3\n"),
+        "Unexpected stderr: {stderr}"
+    );
+    assert!(
+        stderr.contains("[TRACE vvlang::core::parser] Parsed statement at line 1: "),
+        "Unexpected stderr: {stderr}"
+    );
+    assert!(
+        stderr.contains("[DEBUG vvlang::core::parser] Parsed 3 statement(s), found 0 error(s)\n"),
+        "Unexpected stderr: {stderr}"
+    );
+}
+
+#[test]
+fn test_version() {
+    let output = run_vvlang(&["--version"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(stdout, format!("vvlang {}\n", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn test_version_verbose() {
+    let output = run_vvlang(&["--version", "--verbose"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.starts_with(&format!("vvlang {}\ncommit: ", env!("CARGO_PKG_VERSION"))));
+    assert!(stdout.contains("\nbuilt: "), "Unexpected stdout: {stdout}");
+    assert!(stdout.contains("\ntarget: "), "Unexpected stdout: {stdout}");
+}
+
+/// Wait until the watching process says it's waiting for changes again.
+fn wait_until_watching(stderr: &std::sync::mpsc::Receiver<String>) {
+    loop {
+        let line = stderr
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("Timed out waiting for the script to run");
+        if line.starts_with("Watching") {
+            return;
+        }
+    }
+}
+
+#[test]
+fn test_watch() {
+    let dir = std::env::temp_dir().join(format!("vvlang-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("script.vv");
+    std::fs::write(&script, "let x = 5;\n").unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_vvz-lang"))
+        .args(["--ast", "--watch", script.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run the vvlang binary");
+
+    let (sender, stderr) = std::sync::mpsc::channel();
+    let reader = BufReader::new(child.stderr.take().unwrap());
+    std::thread::spawn(move || {
+        for line in reader.lines().map_while(Result::ok) {
+            let _ = sender.send(line);
+        }
+    });
+
+    wait_until_watching(&stderr);
+    // Written twice in a row, like some editors do
+    std::fs::write(&script, "let y = 6;\n").unwrap();
+    std::fs::write(&script, "let y = 7;\n").unwrap();
+    wait_until_watching(&stderr);
+
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "let x = 5;\nlet y = 7;\n");
+}
+
+#[test]
+fn test_fmt() {
+    let output = run_vvlang_with_stdin(&["fmt"], "let x=1+2 ;\nreturn   x;");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "let x = 1 + 2;\nreturn x;\n"
+    );
+}
+
+#[test]
+fn test_fmt_keeps_comments() {
+    let output = run_vvlang_with_stdin(&["fmt"], "let x = 1; // keep me\n// header\nx += 2;\n");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "let x = 1; // keep me\n// header\nx += 2;\n"
+    );
+}
+
+/// Each 'foo.vv' in tests/fixtures/fmt is formatted into 'foo.expected'
+#[test]
+fn test_fmt_fixtures() {
+    for name in ["shebang", "blank_lines"] {
+        let script = format!("tests/fixtures/fmt/{name}.vv");
+        let expected =
+            std::fs::read_to_string(format!("tests/fixtures/fmt/{name}.expected")).unwrap();
+        let output = run_vvlang(&["fmt", &script]);
+
+        assert!(output.status.success(), "Failed to format {script}");
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            expected,
+            "{script}"
+        );
+    }
+}
+
+#[test]
+fn test_fmt_check() {
+    let formatted = run_vvlang_with_stdin(&["fmt", "--check"], "let x = 1;\n");
+    assert!(formatted.status.success());
+
+    let unformatted = run_vvlang_with_stdin(&["fmt", "--check"], "let x=1;\n");
+    assert_eq!(unformatted.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8_lossy(&unformatted.stderr),
+        "<stdin> is not formatted\n"
+    );
+    assert!(unformatted.stdout.is_empty());
+}
+
+#[test]
+fn test_fmt_write() {
+    let dir = std::env::temp_dir().join(format!("vvlang-fmt-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("script.vv");
+    std::fs::write(&script, "#!/usr/bin/env vvz-lang\nlet x=1;\n\nreturn x;\n").unwrap();
+
+    let output = run_vvlang(&["fmt", "--write", script.to_str().unwrap()]);
+    let content = std::fs::read_to_string(&script).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.status.success());
+    // Nothing but the formatting changes, the script can still be run
+    assert_eq!(
+        content,
+        "#!/usr/bin/env vvz-lang\nlet x = 1;\n\nreturn x;\n"
+    );
+}
+
+#[test]
+fn test_fmt_parse_error() {
+    let output = run_vvlang(&["fmt", "--no-color", "tests/fixtures/parse_error.vv"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_highlight_html() {
+    let output = run_vvlang(&[
+        "highlight",
+        "--format",
+        "html",
+        "tests/fixtures/highlight/sample.vv",
+    ]);
+    let expected = std::fs::read_to_string("tests/fixtures/highlight/sample.html").unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+}
@@ -0,0 +1,122 @@
+//! End-to-end tests driven by the scripts in tests/fixtures/cases.
+//! Each 'foo.vv' comes with either 'foo.expected', holding what running it
+//! prints to stdout, or 'foo.err.expected', holding the diagnostics it
+//! prints to stderr when it's expected to fail.
+//! Run with UPDATE_EXPECT=1 to write the expectations from the actual output.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const CASES_DIR: &str = "tests/fixtures/cases";
+
+/// What running a fixture should produce
+enum Expectation {
+    Stdout(PathBuf),
+    Errors(PathBuf),
+}
+
+/// Find the expectation for the fixture at ``script``, if there's one.
+fn expectation_for(script: &Path) -> Option<Expectation> {
+    let stdout = script.with_extension("expected");
+    let errors = script.with_extension("err.expected");
+    if stdout.exists() {
+        Some(Expectation::Stdout(stdout))
+    } else if errors.exists() {
+        Some(Expectation::Errors(errors))
+    } else {
+        None
+    }
+}
+
+/// All the fixtures, sorted by path.
+fn fixtures() -> Vec<PathBuf> {
+    let mut scripts: Vec<PathBuf> = std::fs::read_dir(CASES_DIR)
+        .expect("Failed to list the fixtures")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "vv"))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+/// Take ``script`` through the pipeline, printing the parsed statements.
+// TODO: run the scripts for real once they can be evaluated
+fn run_fixture(script: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vvz-lang"))
+        .args(["--ast", "--no-color"])
+        .arg(script)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("Failed to run the vvlang binary")
+}
+
+/// Write the expectation of ``script`` from what it actually printed,
+/// replacing any previous one.
+fn update_expectation(script: &Path, output: &Output) {
+    let _ = std::fs::remove_file(script.with_extension("expected"));
+    let _ = std::fs::remove_file(script.with_extension("err.expected"));
+
+    let (path, content) = if output.status.success() {
+        (script.with_extension("expected"), &output.stdout)
+    } else {
+        (script.with_extension("err.expected"), &output.stderr)
+    };
+    std::fs::write(path, content).expect("Failed to write the expectation");
+}
+
+/// Compare what ``script`` printed with its expectation,
+/// returning a description of the mismatch if there's one.
+fn check_fixture(script: &Path, output: &Output) -> Result<(), String> {
+    let name = script.display();
+    let (path, actual, should_succeed) = match expectation_for(script) {
+        Some(Expectation::Stdout(path)) => (path, &output.stdout, true),
+        Some(Expectation::Errors(path)) => (path, &output.stderr, false),
+        None => return Err(format!("{name}: no expectation, run with UPDATE_EXPECT=1")),
+    };
+
+    if output.status.success() != should_succeed {
+        return Err(format!(
+            "{name}: expected to {}, but exited with {}\nstderr:\n{}",
+            if should_succeed { "succeed" } else { "fail" },
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let expected = std::fs::read_to_string(&path).expect("Failed to read the expectation");
+    let actual = String::from_utf8_lossy(actual);
+    if actual != expected {
+        return Err(format!(
+            "{name}: output doesn't match {}\n--- expected\n{expected}--- actual\n{actual}",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_fixtures() {
+    let update = std::env::var_os("UPDATE_EXPECT").is_some_and(|v| !v.is_empty());
+    let scripts = fixtures();
+    assert!(!scripts.is_empty(), "No fixtures found in {CASES_DIR}");
+
+    let failures: Vec<String> = scripts
+        .iter()
+        .filter_map(|script| {
+            let output = run_fixture(script);
+            if update {
+                update_expectation(script, &output);
+                return None;
+            }
+            check_fixture(script, &output).err()
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} fixture(s) failed:\n\n{}",
+        failures.len(),
+        scripts.len(),
+        failures.join("\n\n")
+    );
+}
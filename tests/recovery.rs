@@ -0,0 +1,126 @@
+//! Golden tests for how the parser recovers from errors: each case pins down
+//! the full list of diagnostics that a malformed program produces, in order.
+//! After an error, the parser skips to the end of the broken statement,
+//! so each mistake should be reported once.
+
+use indoc::indoc;
+use vvlang::Parser;
+
+/// A diagnostic as (line, column, message), with the column starting from 1
+/// like the CLI shows it.
+type Diagnostic = (usize, usize, String);
+
+/// Everything the parser reports while parsing ``source``.
+fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let mut parser = Parser::new(source).unwrap();
+    parser.parse_program();
+    parser
+        .errors
+        .iter()
+        .map(|e| (e.line_num, e.char_offset + 1, e.message.clone()))
+        .collect()
+}
+
+/// Check that parsing ``source`` reports exactly the ``expected`` diagnostics.
+fn assert_diagnostics(source: &str, expected: &[(usize, usize, &str)]) {
+    let expected: Vec<Diagnostic> = expected
+        .iter()
+        .map(|&(line, column, message)| (line, column, message.to_owned()))
+        .collect();
+    let actual = diagnostics(source);
+    if actual != expected {
+        panic!(
+            "Unexpected diagnostics for:\n{source}\nexpected:\n{}\nactual:\n{}",
+            format_diagnostics(&expected),
+            format_diagnostics(&actual)
+        );
+    }
+}
+
+/// One diagnostic per line, so that mismatches are easy to spot.
+fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|(line, column, message)| format!("  {line}:{column}: {message}\n"))
+        .collect()
+}
+
+#[test]
+fn test_valid_program_has_no_diagnostics() {
+    assert_diagnostics("let x = 5;\nreturn x;\n", &[]);
+}
+
+#[test]
+fn test_missing_assign() {
+    assert_diagnostics("let x 5;\n", &[(1, 7, "Expected '=', found '5'")]);
+}
+
+#[test]
+fn test_resync_at_next_statement_without_semicolon() {
+    assert_diagnostics(
+        "let x 5\nlet = 6;\nreturn x;\n",
+        &[
+            (1, 7, "Expected '=', found '5'"),
+            (2, 5, "Expected identifier, found '='"),
+        ],
+    );
+}
+
+#[test]
+fn test_missing_semicolon() {
+    assert_diagnostics(
+        "let x = 5;\nreturn x\n",
+        &[(3, 1, "Expected ';', found end of file (EOF)")],
+    );
+}
+
+#[test]
+fn test_statement_ending_at_eof_without_semicolon() {
+    // Nothing after the statement, not even a newline
+    assert_diagnostics(
+        "let x = 5;\nreturn x",
+        &[(2, 9, "Expected ';', found end of file (EOF)")],
+    );
+    assert_diagnostics(
+        "let x = 5;\nx = 6",
+        &[(2, 6, "Expected ';', found end of file (EOF)")],
+    );
+}
+
+#[test]
+fn test_lone_token_at_eof() {
+    assert_diagnostics("let x = 5;\nx", &[(2, 1, "Unsupported token: 'x'")]);
+}
+
+#[test]
+fn test_unexpected_token_mid_statement() {
+    assert_diagnostics(
+        "let x = 1;\nlet y ) 2;\nreturn y;\n",
+        &[(2, 7, "Unmatched ')'")],
+    );
+}
+
+#[test]
+fn test_multiple_errors() {
+    let source = indoc! {"
+        let x = 1;
+        let 5 = 2;
+        let y 3;
+        else;
+        let z = (x];
+        return;
+    "};
+    assert_diagnostics(
+        source,
+        &[
+            (2, 5, "Expected identifier, found '5'"),
+            (3, 7, "Expected '=', found '3'"),
+            (4, 1, "'else' can only follow the block of an 'if'"),
+            (
+                5,
+                11,
+                "Mismatched delimiter: expected ')' to close '(' at line 5, found ']'",
+            ),
+        ],
+    );
+}